@@ -0,0 +1,439 @@
+//! Installed application catalog for Luna.
+//!
+//! Scans the platform's application directories so spoken names resolve to
+//! apps that actually exist: `.app` bundles in `/Applications` and
+//! `~/Applications` on macOS, `.desktop` entries under the XDG data dirs on
+//! Linux. Scan results are cached as JSON and rescanned when a scanned
+//! directory changes.
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// An installed application.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppEntry {
+    /// Display name ("Visual Studio Code")
+    pub name: String,
+    /// Desktop entry ID on Linux ("code"), bundle path on macOS
+    pub id: String,
+    /// File the entry was read from
+    pub path: PathBuf,
+    /// Program the entry runs, which is usually its process name ("code")
+    #[serde(default)]
+    pub exec: Option<String>,
+}
+
+/// Outcome of resolving a spoken app name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution<'a> {
+    /// Exactly one app matches
+    Found(&'a AppEntry),
+    /// Several apps match equally well
+    Ambiguous(Vec<&'a AppEntry>),
+    /// No installed app matches
+    NotFound,
+}
+
+/// Catalog of installed applications.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppCatalog {
+    /// Directories that were scanned
+    pub dirs: Vec<PathBuf>,
+    /// Apps found, in scan order
+    pub apps: Vec<AppEntry>,
+}
+
+impl AppCatalog {
+    /// Scan the current platform's application directories, using the cache
+    /// when it is still fresh.
+    pub fn load_default() -> Self {
+        let dirs = default_dirs();
+        match cache_path() {
+            Some(cache) => Self::load_cached(&dirs, &cache),
+            None => Self::scan(&dirs),
+        }
+    }
+
+    /// Load the catalog from `cache` if it was written after every directory
+    /// in `dirs` last changed; otherwise scan and rewrite the cache.
+    ///
+    /// Cache failures are never fatal: the worst case is a fresh scan.
+    pub fn load_cached(dirs: &[PathBuf], cache: &Path) -> Self {
+        if let Some(catalog) = read_cache(dirs, cache) {
+            return catalog;
+        }
+
+        let catalog = Self::scan(dirs);
+        if let Ok(json) = serde_json::to_string(&catalog) {
+            let _ = cache.parent().map(fs::create_dir_all);
+            let _ = fs::write(cache, json);
+        }
+        catalog
+    }
+
+    /// Scan directories for `.app` bundles and `.desktop` files.
+    ///
+    /// Earlier directories take precedence when the same ID appears twice,
+    /// matching XDG lookup order.
+    pub fn scan(dirs: &[PathBuf]) -> Self {
+        let mut apps: Vec<AppEntry> = Vec::new();
+        for dir in dirs {
+            for entry in scan_dir(dir, dir) {
+                if !apps.iter().any(|app| app.id == entry.id) {
+                    apps.push(entry);
+                }
+            }
+        }
+
+        Self {
+            dirs: dirs.to_vec(),
+            apps,
+        }
+    }
+
+    /// Whether the catalog has no apps.
+    pub fn is_empty(&self) -> bool {
+        self.apps.is_empty()
+    }
+
+    /// Display names of all apps.
+    pub fn names(&self) -> Vec<String> {
+        self.apps.iter().map(|app| app.name.clone()).collect()
+    }
+
+    /// Find an app by exact display name or ID (ignoring case).
+    pub fn get(&self, name: &str) -> Option<&AppEntry> {
+        self.apps
+            .iter()
+            .find(|app| app.name.eq_ignore_ascii_case(name))
+            .or_else(|| self.apps.iter().find(|app| app.id.eq_ignore_ascii_case(name)))
+    }
+
+    /// Resolve a spoken name to an installed app.
+    ///
+    /// An exact name or ID match wins. Otherwise every app whose name
+    /// contains all the spoken words is a match ("chrome" finds "Google
+    /// Chrome"); more than one such app is reported as ambiguous.
+    pub fn resolve(&self, spoken: &str) -> Resolution<'_> {
+        if let Some(app) = self.get(spoken.trim()) {
+            return Resolution::Found(app);
+        }
+
+        let spoken_words = words(spoken);
+        if spoken_words.is_empty() {
+            return Resolution::NotFound;
+        }
+
+        let mut matches: Vec<&AppEntry> = self
+            .apps
+            .iter()
+            .filter(|app| {
+                let name_words = words(&app.name);
+                spoken_words.iter().all(|w| name_words.contains(w))
+            })
+            .collect();
+
+        match matches.len() {
+            0 => Resolution::NotFound,
+            1 => Resolution::Found(matches.remove(0)),
+            _ => Resolution::Ambiguous(matches),
+        }
+    }
+}
+
+/// Lowercase words of a name.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Application directories for the current platform.
+pub fn default_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    if cfg!(target_os = "macos") {
+        let mut dirs: Vec<PathBuf> = [
+            "/Applications",
+            "/Applications/Utilities",
+            "/System/Applications",
+            "/System/Applications/Utilities",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        dirs.extend(home.map(|h| h.join("Applications")));
+        return dirs;
+    }
+
+    // $XDG_DATA_HOME first, then $XDG_DATA_DIRS, each with "applications"
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|h| h.join(".local").join("share")));
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    data_home
+        .into_iter()
+        .chain(env::split_paths(&data_dirs))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Location of the catalog cache (`$XDG_CACHE_HOME/luna/apps.json`).
+pub fn cache_path() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .map(|dir| dir.join("luna").join("apps.json"))
+}
+
+/// Read the cache if it covers `dirs` and is newer than all of them.
+fn read_cache(dirs: &[PathBuf], cache: &Path) -> Option<AppCatalog> {
+    let cached_at = fs::metadata(cache).and_then(|m| m.modified()).ok()?;
+    let is_stale = dirs.iter().any(|dir| modified(dir).is_some_and(|t| t > cached_at));
+    if is_stale {
+        return None;
+    }
+
+    let catalog: AppCatalog = serde_json::from_str(&fs::read_to_string(cache).ok()?).ok()?;
+    (catalog.dirs == dirs).then_some(catalog)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Collect app entries in `dir`, descending into subdirectories for
+/// `.desktop` files (whose IDs then include the subdirectory, per XDG).
+fn scan_dir(root: &Path, dir: &Path) -> Vec<AppEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+
+    let mut apps = Vec::new();
+    for path in paths {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("app") => apps.extend(read_bundle(&path)),
+            Some("desktop") => apps.extend(read_desktop_file(root, &path)),
+            _ if path.is_dir() => apps.extend(scan_dir(root, &path)),
+            _ => {}
+        }
+    }
+    apps
+}
+
+/// An `.app` bundle: the name is the bundle's file stem.
+fn read_bundle(path: &Path) -> Option<AppEntry> {
+    let name = path.file_stem()?.to_str()?.to_string();
+    Some(AppEntry {
+        name,
+        id: path.to_string_lossy().to_string(),
+        path: path.to_path_buf(),
+        exec: None,
+    })
+}
+
+/// A `.desktop` file, skipped unless it is a visible application.
+fn read_desktop_file(root: &Path, path: &Path) -> Option<AppEntry> {
+    let contents = fs::read_to_string(path).ok()?;
+    let (name, exec) = parse_desktop_entry(&contents)?;
+
+    // "kde/org.kde.dolphin.desktop" has ID "kde-org.kde.dolphin"
+    let relative = path.strip_prefix(root).ok()?.with_extension("");
+    let id = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Some(AppEntry {
+        name,
+        id,
+        path: path.to_path_buf(),
+        exec,
+    })
+}
+
+/// Parse the `[Desktop Entry]` group, returning the app's `Name` and the
+/// program its `Exec` line runs if it is a visible application.
+fn parse_desktop_entry(contents: &str) -> Option<(String, Option<String>)> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut is_application = false;
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (key.trim(), value.trim()) {
+            ("Name", value) => name = Some(value.to_string()),
+            ("Exec", value) => exec = exec_program(value),
+            ("Type", value) => is_application = value == "Application",
+            ("NoDisplay" | "Hidden", "true") => return None,
+            _ => {}
+        }
+    }
+
+    name.filter(|_| is_application).map(|name| (name, exec))
+}
+
+/// The program an `Exec` line runs: "env FOO=1 /usr/bin/code %F" -> "code".
+fn exec_program(exec: &str) -> Option<String> {
+    let program = exec
+        .split_whitespace()
+        .map(|word| word.trim_matches('"'))
+        .find(|word| *word != "env" && !word.contains('='))?;
+    Path::new(program).file_name().map(|name| name.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh fixture directory under the system temp dir.
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("luna-catalog-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_desktop(dir: &Path, file: &str, body: &str) {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, body).unwrap();
+    }
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let entry = "[Desktop Entry]\nType=Application\nName=Firefox\nName[de]=Feuerfuchs\nExec=/usr/lib/firefox/firefox %u\n\n[Desktop Action new]\nName=New Window\nExec=firefox --new-window\n";
+        assert_eq!(
+            parse_desktop_entry(entry),
+            Some(("Firefox".to_string(), Some("firefox".to_string())))
+        );
+        assert_eq!(
+            parse_desktop_entry("[Desktop Entry]\nType=Link\nName=Docs\n"),
+            None
+        );
+        assert_eq!(
+            parse_desktop_entry("[Desktop Entry]\nType=Application\nName=Helper\nNoDisplay=true\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_exec_program() {
+        assert_eq!(exec_program("code --unity-launch %F"), Some("code".to_string()));
+        assert_eq!(exec_program("env BAMF_DESKTOP_FILE_HINT=x /snap/bin/spotify %U"), Some("spotify".to_string()));
+        assert_eq!(exec_program(""), None);
+    }
+
+    #[test]
+    fn test_scan_linux_fixture() {
+        let dir = fixture_dir("linux");
+        write_desktop(&dir, "code.desktop", "[Desktop Entry]\nType=Application\nName=Visual Studio Code\n");
+        write_desktop(&dir, "firefox.desktop", "[Desktop Entry]\nType=Application\nName=Firefox\n");
+        write_desktop(&dir, "kde/org.kde.dolphin.desktop", "[Desktop Entry]\nType=Application\nName=Dolphin\n");
+        write_desktop(&dir, "hidden.desktop", "[Desktop Entry]\nType=Application\nName=Hidden\nHidden=true\n");
+
+        let catalog = AppCatalog::scan(std::slice::from_ref(&dir));
+        let ids: Vec<_> = catalog.apps.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["code", "firefox", "kde-org.kde.dolphin"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_macos_fixture() {
+        let dir = fixture_dir("macos");
+        fs::create_dir_all(dir.join("Safari.app/Contents")).unwrap();
+        fs::create_dir_all(dir.join("Visual Studio Code.app")).unwrap();
+        fs::write(dir.join("README.txt"), "").unwrap();
+
+        let catalog = AppCatalog::scan(std::slice::from_ref(&dir));
+        assert_eq!(catalog.names(), vec!["Safari", "Visual Studio Code"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_precedence() {
+        let user = fixture_dir("precedence-user");
+        let system = fixture_dir("precedence-system");
+        write_desktop(&user, "firefox.desktop", "[Desktop Entry]\nType=Application\nName=Firefox Nightly\n");
+        write_desktop(&system, "firefox.desktop", "[Desktop Entry]\nType=Application\nName=Firefox\n");
+
+        let catalog = AppCatalog::scan(&[user.clone(), system.clone()]);
+        assert_eq!(catalog.names(), vec!["Firefox Nightly"]);
+
+        fs::remove_dir_all(user).unwrap();
+        fs::remove_dir_all(system).unwrap();
+    }
+
+    #[test]
+    fn test_resolve() {
+        let app = |name: &str, id: &str| AppEntry {
+            name: name.to_string(),
+            id: id.to_string(),
+            path: PathBuf::new(),
+            exec: None,
+        };
+        let catalog = AppCatalog {
+            dirs: Vec::new(),
+            apps: vec![
+                app("Visual Studio Code", "code"),
+                app("Google Chrome", "google-chrome"),
+                app("Chrome Remote Desktop", "chrome-remote-desktop"),
+                app("Firefox", "firefox"),
+            ],
+        };
+
+        assert_eq!(catalog.resolve("firefox"), Resolution::Found(&catalog.apps[3]));
+        assert_eq!(catalog.resolve("code"), Resolution::Found(&catalog.apps[0]));
+        assert_eq!(catalog.resolve("studio"), Resolution::Found(&catalog.apps[0]));
+        assert_eq!(
+            catalog.resolve("chrome"),
+            Resolution::Ambiguous(vec![&catalog.apps[1], &catalog.apps[2]])
+        );
+        assert_eq!(catalog.resolve("photoshop"), Resolution::NotFound);
+    }
+
+    #[test]
+    fn test_load_cached() {
+        let dir = fixture_dir("cache-apps");
+        let cache = fixture_dir("cache-file").join("apps.json");
+        write_desktop(&dir, "firefox.desktop", "[Desktop Entry]\nType=Application\nName=Firefox\n");
+        let dirs = vec![dir.clone()];
+
+        let scanned = AppCatalog::load_cached(&dirs, &cache);
+        assert!(cache.exists());
+        assert_eq!(read_cache(&dirs, &cache), Some(scanned.clone()));
+
+        // A cache for different directories is ignored
+        assert_eq!(read_cache(&[PathBuf::from("/elsewhere")], &cache), None);
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(cache.parent().unwrap()).unwrap();
+    }
+}
@@ -2,24 +2,123 @@
 //!
 //! Handles command-line interface using clap derive macros.
 
-use clap::Parser;
+use luna::intent::parse::DEFAULT_CANDIDATES;
+use clap::{Parser, Subcommand};
+#[cfg(feature = "http")]
+use std::net::IpAddr;
+use std::path::PathBuf;
 
-/// Luna - Natural language system control for macOS
+/// Luna - Natural language system control for macOS and Linux
 #[derive(Parser, Debug)]
 #[command(name = "luna")]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
-    /// Natural language command to execute
-    #[arg(required = true)]
-    pub command: String,
+    #[command(subcommand)]
+    pub subcommand: Option<Command>,
+
+    /// Natural language command to execute; without one, starts the REPL
+    pub command: Option<String>,
 
     /// Parse and print intent without executing
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub dry_run: bool,
 
     /// Output result in JSON format
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub json: bool,
+
+    /// Number of ranked intent candidates to include in JSON output
+    #[arg(long, global = true, default_value_t = DEFAULT_CANDIDATES)]
+    pub candidates: usize,
+
+    /// Keep running the remaining steps of a compound command after one fails
+    #[arg(long, global = true)]
+    pub keep_going: bool,
+
+    /// Run actions that need confirmation (force quit, restart, shut down, ...) without asking
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Read the result aloud (`say` on macOS, `espeak-ng` on Linux)
+    #[arg(long, global = true)]
+    pub speak: bool,
+}
+
+/// Subcommands for managing Luna itself.
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Manage app aliases (~/.config/luna/aliases.toml)
+    #[command(subcommand)]
+    Alias(AliasCommand),
+
+    /// Read and run commands line by line, keeping context between them
+    Repl,
+
+    /// Answer JSON-RPC requests (parse, dry_run, execute) on a Unix socket
+    Daemon {
+        /// Socket path (default: $XDG_RUNTIME_DIR/luna.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
+    /// Send a command to a running daemon and print its JSON reply
+    Client {
+        /// Natural language command
+        input: String,
+
+        /// Only parse the command, without planning or running it
+        #[arg(long)]
+        parse: bool,
+
+        /// Socket path (default: $XDG_RUNTIME_DIR/luna.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
+    /// Serve Luna's actions as MCP tools over stdio (honors --dry-run and --yes)
+    Mcp,
+
+    /// Serve the HTTP API (POST /v1/command, POST /v1/parse, GET /v1/actions)
+    #[cfg(feature = "http")]
+    Serve {
+        /// Address to listen on; anything but loopback needs a token
+        #[arg(long, default_value = "127.0.0.1")]
+        address: IpAddr,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = luna::http::DEFAULT_PORT)]
+        port: u16,
+
+        /// Bearer token clients must send (default: $LUNA_HTTP_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Print the OpenAPI document and exit
+        #[arg(long)]
+        openapi: bool,
+    },
+}
+
+/// `luna alias` subcommands.
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum AliasCommand {
+    /// Add or replace an alias: luna alias add code "Visual Studio Code"
+    Add {
+        /// Spoken name
+        alias: String,
+        /// App name (or URL) to open instead
+        target: String,
+    },
+
+    /// List all aliases
+    List,
+
+    /// Remove an alias
+    Remove {
+        /// Spoken name
+        alias: String,
+    },
 }
 
 impl Cli {
@@ -36,9 +135,11 @@ mod tests {
     #[test]
     fn test_cli_parsing() {
         let cli = Cli::try_parse_from(["luna", "open safari"]).unwrap();
-        assert_eq!(cli.command, "open safari");
+        assert_eq!(cli.command.as_deref(), Some("open safari"));
+        assert_eq!(cli.subcommand, None);
         assert!(!cli.dry_run);
         assert!(!cli.json);
+        assert_eq!(cli.candidates, DEFAULT_CANDIDATES);
     }
 
     #[test]
@@ -52,4 +153,98 @@ mod tests {
         let cli = Cli::try_parse_from(["luna", "--json", "set volume to 40"]).unwrap();
         assert!(cli.json);
     }
+
+    #[test]
+    fn test_cli_keep_going() {
+        let cli = Cli::try_parse_from(["luna", "--keep-going", "mute and open safari"]).unwrap();
+        assert!(cli.keep_going);
+    }
+
+    #[test]
+    fn test_cli_yes() {
+        assert!(Cli::try_parse_from(["luna", "-y", "kill zoom"]).unwrap().yes);
+        assert!(Cli::try_parse_from(["luna", "--yes", "kill zoom"]).unwrap().yes);
+        assert!(!Cli::try_parse_from(["luna", "kill zoom"]).unwrap().yes);
+    }
+
+    #[test]
+    fn test_cli_speak() {
+        assert!(Cli::try_parse_from(["luna", "--speak", "mute"]).unwrap().speak);
+        assert!(!Cli::try_parse_from(["luna", "mute"]).unwrap().speak);
+    }
+
+    #[test]
+    fn test_cli_alias_subcommands() {
+        let cli = Cli::try_parse_from(["luna", "alias", "add", "code", "Visual Studio Code"]).unwrap();
+        assert_eq!(
+            cli.subcommand,
+            Some(Command::Alias(AliasCommand::Add {
+                alias: "code".to_string(),
+                target: "Visual Studio Code".to_string(),
+            }))
+        );
+        assert_eq!(cli.command, None);
+
+        let cli = Cli::try_parse_from(["luna", "alias", "list", "--json"]).unwrap();
+        assert_eq!(cli.subcommand, Some(Command::Alias(AliasCommand::List)));
+        assert!(cli.json);
+
+        let cli = Cli::try_parse_from(["luna", "alias", "remove", "code"]).unwrap();
+        assert_eq!(
+            cli.subcommand,
+            Some(Command::Alias(AliasCommand::Remove { alias: "code".to_string() }))
+        );
+    }
+
+    #[test]
+    fn test_cli_command_containing_subcommand_name() {
+        let cli = Cli::try_parse_from(["luna", "alias list"]).unwrap();
+        assert_eq!(cli.command.as_deref(), Some("alias list"));
+        assert_eq!(cli.subcommand, None);
+    }
+
+    #[test]
+    fn test_cli_repl() {
+        let cli = Cli::try_parse_from(["luna"]).unwrap();
+        assert_eq!((cli.subcommand, cli.command), (None, None));
+
+        let cli = Cli::try_parse_from(["luna", "repl", "--dry-run", "--json"]).unwrap();
+        assert_eq!(cli.subcommand, Some(Command::Repl));
+        assert!(cli.dry_run && cli.json);
+    }
+
+    #[test]
+    fn test_cli_daemon_and_client() {
+        let cli = Cli::try_parse_from(["luna", "daemon", "--socket", "/tmp/l.sock"]).unwrap();
+        assert_eq!(cli.subcommand, Some(Command::Daemon { socket: Some(PathBuf::from("/tmp/l.sock")) }));
+
+        let cli = Cli::try_parse_from(["luna", "client", "--dry-run", "open safari"]).unwrap();
+        assert_eq!(
+            cli.subcommand,
+            Some(Command::Client { input: "open safari".to_string(), parse: false, socket: None })
+        );
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_cli_mcp() {
+        let cli = Cli::try_parse_from(["luna", "mcp", "--dry-run"]).unwrap();
+        assert_eq!(cli.subcommand, Some(Command::Mcp));
+        assert!(cli.dry_run);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_cli_serve() {
+        let cli = Cli::try_parse_from(["luna", "serve", "--port", "9000"]).unwrap();
+        assert_eq!(
+            cli.subcommand,
+            Some(Command::Serve {
+                address: IpAddr::from([127, 0, 0, 1]),
+                port: 9000,
+                token: None,
+                openapi: false,
+            })
+        );
+    }
 }
@@ -0,0 +1,179 @@
+//! App aliases for Luna.
+//!
+//! Maps spoken names to real app names, so "open code" launches
+//! "Visual Studio Code" instead of an app called "Code". Stored as TOML:
+//!
+//! ```toml
+//! [apps]
+//! code = "Visual Studio Code"
+//! iterm = "iTerm"
+//! ```
+
+use super::{config_dir, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// File name of the alias table inside the config directory.
+pub const ALIASES_FILE: &str = "aliases.toml";
+
+/// Spoken-name to app-name aliases.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasTable {
+    /// Spoken name (lowercase) -> app name or URL
+    #[serde(default)]
+    pub apps: BTreeMap<String, String>,
+}
+
+impl AliasTable {
+    /// Default location of the alias file (`~/.config/luna/aliases.toml`).
+    pub fn default_path() -> Result<PathBuf, ConfigError> {
+        Ok(config_dir()?.join(ALIASES_FILE))
+    }
+
+    /// Load the alias table from the default location.
+    pub fn load_default() -> Result<Self, ConfigError> {
+        Self::load(&Self::default_path()?)
+    }
+
+    /// Load an alias table; a missing file is an empty table.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let mut table: AliasTable = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        // Keys are matched against normalized input
+        table.apps = table
+            .apps
+            .into_iter()
+            .map(|(alias, target)| (normalize_alias(&alias), target))
+            .collect();
+        Ok(table)
+    }
+
+    /// Write the alias table, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let io_error = |source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?).map_err(io_error)
+    }
+
+    /// Add or replace an alias; returns the previous target, if any.
+    pub fn add(&mut self, alias: &str, target: &str) -> Option<String> {
+        self.apps
+            .insert(normalize_alias(alias), target.trim().to_string())
+    }
+
+    /// Remove an alias; returns its target, if it existed.
+    pub fn remove(&mut self, alias: &str) -> Option<String> {
+        self.apps.remove(&normalize_alias(alias))
+    }
+
+    /// Look up the target for a spoken name.
+    pub fn resolve(&self, spoken: &str) -> Option<&str> {
+        self.apps.get(&normalize_alias(spoken)).map(String::as_str)
+    }
+
+    /// Whether `name` is the target of some alias.
+    pub fn is_target(&self, name: &str) -> bool {
+        self.apps.values().any(|target| target.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Lowercase and collapse whitespace, matching how input is normalized.
+fn normalize_alias(alias: &str) -> String {
+    alias
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("luna-aliases-{}-{}", std::process::id(), name))
+            .join(ALIASES_FILE)
+    }
+
+    #[test]
+    fn test_add_resolve_remove() {
+        let mut table = AliasTable::default();
+        assert_eq!(table.add("Code", "Visual Studio Code"), None);
+        assert_eq!(table.resolve("code"), Some("Visual Studio Code"));
+        assert_eq!(table.resolve("  CODE "), Some("Visual Studio Code"));
+        assert!(table.is_target("visual studio code"));
+
+        assert_eq!(table.remove("code"), Some("Visual Studio Code".to_string()));
+        assert_eq!(table.resolve("code"), None);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let table = AliasTable::load(&temp_path("missing")).unwrap();
+        assert!(table.apps.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut table = AliasTable::default();
+        table.add("code", "Visual Studio Code");
+        table.add("iterm", "iTerm");
+        table.save(&path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("[apps]"));
+        assert_eq!(AliasTable::load(&path).unwrap(), table);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_load_normalizes_keys() {
+        let path = temp_path("normalize");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[apps]\n\"My  Editor\" = \"Zed\"\n").unwrap();
+
+        let table = AliasTable::load(&path).unwrap();
+        assert_eq!(table.resolve("my editor"), Some("Zed"));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let path = temp_path("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "apps = 3").unwrap();
+
+        assert!(matches!(
+            AliasTable::load(&path),
+            Err(ConfigError::Parse { .. })
+        ));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
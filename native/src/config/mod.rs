@@ -0,0 +1,56 @@
+//! Configuration module for Luna.
+//!
+//! User configuration lives in `$XDG_CONFIG_HOME/luna` (usually
+//! `~/.config/luna`); `LUNA_CONFIG_DIR` overrides the location.
+
+pub mod aliases;
+pub mod search;
+pub mod snippets;
+
+// Re-export commonly used items
+pub use aliases::AliasTable;
+pub use search::SearchEngines;
+pub use snippets::Snippets;
+
+use std::env;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors that can occur while loading or saving configuration.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Could not determine the config directory. Set HOME or LUNA_CONFIG_DIR.")]
+    NoConfigDir,
+
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Failed to serialize config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Directory holding Luna's configuration files.
+///
+/// Uses `LUNA_CONFIG_DIR` if set, then `$XDG_CONFIG_HOME/luna`, then
+/// `~/.config/luna`.
+pub fn config_dir() -> Result<PathBuf, ConfigError> {
+    if let Some(dir) = env::var_os("LUNA_CONFIG_DIR").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("luna"));
+    }
+    env::var_os("HOME")
+        .filter(|h| !h.is_empty())
+        .map(|home| PathBuf::from(home).join(".config").join("luna"))
+        .ok_or(ConfigError::NoConfigDir)
+}
@@ -0,0 +1,211 @@
+//! Search engines for Luna.
+//!
+//! Each engine is a URL template with a `{query}` placeholder. Built-in
+//! engines can be overridden and new ones added in `search.toml`:
+//!
+//! ```toml
+//! default = "kagi"
+//!
+//! [engines]
+//! docs = "https://docs.example.com/search?q={query}"
+//! ```
+
+use super::{config_dir, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// File name of the search engine config inside the config directory.
+pub const SEARCH_FILE: &str = "search.toml";
+
+/// Engine used when none is named and the config doesn't choose one.
+pub const DEFAULT_ENGINE: &str = "duckduckgo";
+
+/// Placeholder replaced by the encoded query in engine templates.
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
+/// Template of the default engine, used when no configured engine fits.
+const FALLBACK_TEMPLATE: &str = "https://duckduckgo.com/?q={query}";
+
+/// Engines available without any configuration.
+const BUILTIN_ENGINES: &[(&str, &str)] = &[
+    ("bing", "https://www.bing.com/search?q={query}"),
+    (DEFAULT_ENGINE, FALLBACK_TEMPLATE),
+    ("github", "https://github.com/search?q={query}"),
+    ("google", "https://www.google.com/search?q={query}"),
+    ("kagi", "https://kagi.com/search?q={query}"),
+    ("stackoverflow", "https://stackoverflow.com/search?q={query}"),
+    ("wikipedia", "https://en.wikipedia.org/w/index.php?search={query}"),
+    ("youtube", "https://www.youtube.com/results?search_query={query}"),
+];
+
+/// Named search engine URL templates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchEngines {
+    /// Engine used when the command doesn't name one
+    #[serde(default = "default_engine")]
+    pub default: String,
+    /// Engine name (lowercase, no spaces) -> URL template
+    #[serde(default)]
+    pub engines: BTreeMap<String, String>,
+}
+
+fn default_engine() -> String {
+    DEFAULT_ENGINE.to_string()
+}
+
+impl Default for SearchEngines {
+    fn default() -> Self {
+        Self {
+            default: default_engine(),
+            engines: BUILTIN_ENGINES
+                .iter()
+                .map(|(name, template)| (name.to_string(), template.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl SearchEngines {
+    /// Default location of the search config (`~/.config/luna/search.toml`).
+    pub fn default_path() -> Result<PathBuf, ConfigError> {
+        Ok(config_dir()?.join(SEARCH_FILE))
+    }
+
+    /// Load the search config from the default location.
+    pub fn load_default() -> Result<Self, ConfigError> {
+        Self::load(&Self::default_path()?)
+    }
+
+    /// Load a search config on top of the built-in engines; a missing file
+    /// leaves just the built-ins.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let config: SearchEngines = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let mut engines = Self {
+            default: normalize_engine(&config.default),
+            ..Self::default()
+        };
+        engines.engines.extend(
+            config
+                .engines
+                .into_iter()
+                .map(|(name, template)| (normalize_engine(&name), template)),
+        );
+        Ok(engines)
+    }
+
+    /// Look up an engine by spoken name ("duck duck go" finds "duckduckgo").
+    pub fn get(&self, name: &str) -> Option<(&str, &str)> {
+        self.engines
+            .get_key_value(&normalize_engine(name))
+            .map(|(name, template)| (name.as_str(), template.as_str()))
+    }
+
+    /// Build the search URL for `query` on `engine`, falling back to the
+    /// default engine (then DuckDuckGo) if `engine` is unknown.
+    pub fn url(&self, engine: &str, query: &str) -> String {
+        let template = self
+            .get(engine)
+            .or_else(|| self.get(&self.default))
+            .map_or(FALLBACK_TEMPLATE, |(_, template)| template);
+        template.replace(QUERY_PLACEHOLDER, &percent_encode(query))
+    }
+}
+
+/// Engine names are matched lowercase, ignoring spaces and hyphens.
+fn normalize_engine(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Percent-encode text for a URL query value, keeping only RFC 3986
+/// unreserved characters.
+pub fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("luna-search-{}-{}", std::process::id(), name))
+            .join(SEARCH_FILE)
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("rust lifetimes"), "rust%20lifetimes");
+        assert_eq!(percent_encode("c++ & go?"), "c%2B%2B%20%26%20go%3F");
+        assert_eq!(percent_encode("café"), "caf%C3%A9");
+        assert_eq!(percent_encode("a-b_c.d~e"), "a-b_c.d~e");
+    }
+
+    #[test]
+    fn test_url() {
+        let engines = SearchEngines::default();
+        assert_eq!(
+            engines.url("google", "best pizza"),
+            "https://www.google.com/search?q=best%20pizza"
+        );
+        assert_eq!(
+            engines.url("duck duck go", "rust"),
+            "https://duckduckgo.com/?q=rust"
+        );
+        // Unknown engines use the default
+        assert_eq!(engines.url("altavista", "rust"), "https://duckduckgo.com/?q=rust");
+    }
+
+    #[test]
+    fn test_load_missing_file_is_builtin() {
+        assert_eq!(SearchEngines::load(&temp_path("missing")).unwrap(), SearchEngines::default());
+    }
+
+    #[test]
+    fn test_load_merges_with_builtin() {
+        let path = temp_path("merge");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            "default = \"Kagi\"\n[engines]\n\"My Docs\" = \"https://docs.example.com/?q={query}\"\n",
+        )
+        .unwrap();
+
+        let engines = SearchEngines::load(&path).unwrap();
+        assert_eq!(engines.default, "kagi");
+        assert_eq!(engines.url("my docs", "a b"), "https://docs.example.com/?q=a%20b");
+        assert!(engines.get("google").is_some());
+        assert_eq!(engines.url("nope", "x"), "https://kagi.com/search?q=x");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+//! Text snippets for Luna.
+//!
+//! Named pieces of text that "copy my <name>" puts on the clipboard, so
+//! "copy my email to the clipboard" copies an address instead of the words
+//! "my email". Stored as TOML:
+//!
+//! ```toml
+//! [text]
+//! email = "me@example.com"
+//! "home address" = "1 Infinite Loop, Cupertino"
+//! ```
+
+use super::{config_dir, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// File name of the snippet table inside the config directory.
+pub const SNIPPETS_FILE: &str = "snippets.toml";
+
+/// Named text snippets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippets {
+    /// Spoken name (lowercase) -> text
+    #[serde(default)]
+    pub text: BTreeMap<String, String>,
+}
+
+impl Snippets {
+    /// Default location of the snippet file (`~/.config/luna/snippets.toml`).
+    pub fn default_path() -> Result<PathBuf, ConfigError> {
+        Ok(config_dir()?.join(SNIPPETS_FILE))
+    }
+
+    /// Load the snippets from the default location.
+    ///
+    /// Without an `email` snippet, the conventional `EMAIL` environment
+    /// variable is used.
+    pub fn load_default() -> Result<Self, ConfigError> {
+        let mut snippets = Self::load(&Self::default_path()?)?;
+        if let Some(email) = env::var("EMAIL").ok().filter(|email| !email.is_empty()) {
+            snippets.text.entry("email".to_string()).or_insert(email);
+        }
+        Ok(snippets)
+    }
+
+    /// Load a snippet table; a missing file is an empty table.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(ConfigError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        let mut snippets: Snippets = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        // Keys are matched against normalized input
+        snippets.text = snippets
+            .text
+            .into_iter()
+            .map(|(name, text)| (normalize_name(&name), text))
+            .collect();
+        Ok(snippets)
+    }
+
+    /// Look up a snippet by spoken name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.text.get(&normalize_name(name)).map(String::as_str)
+    }
+}
+
+/// Lowercase and collapse whitespace, matching how input is normalized.
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("luna-snippets-{}-{}", std::process::id(), name))
+            .join(SNIPPETS_FILE)
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        assert_eq!(Snippets::load(&temp_path("missing")).unwrap(), Snippets::default());
+    }
+
+    #[test]
+    fn test_load_normalizes_names() {
+        let path = temp_path("normalize");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[text]\nEmail = \"me@example.com\"\n\"Home  Address\" = \"1 Main St\"\n").unwrap();
+
+        let snippets = Snippets::load(&path).unwrap();
+        assert_eq!(snippets.get("email"), Some("me@example.com"));
+        assert_eq!(snippets.get("home address"), Some("1 Main St"));
+        assert_eq!(snippets.get("phone"), None);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
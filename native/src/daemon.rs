@@ -0,0 +1,488 @@
+//! Background server for Luna.
+//!
+//! `luna daemon` listens on a Unix domain socket and answers JSON-RPC 2.0
+//! requests, one JSON object per line, so scripts and other programs can
+//! drive Luna without paying for config loading and app discovery on every
+//! command. `luna client` is the matching one-shot client.
+//!
+//! Methods take `{"input": "..."}` plus the optional `yes`, `keep_going` and
+//! `candidates` flags of the CLI:
+//!
+//! * `parse` - interpret the input without planning or running anything
+//! * `dry_run` - also show the command that would run
+//! * `execute` - run it
+//!
+//! The result has the same shape as `luna --json`: a `JsonOutput`, or a
+//! `JsonSequenceOutput` for a compound command. Input that can't be parsed is
+//! a result with status "error", not a JSON-RPC error; those are kept for
+//! malformed requests.
+
+use crate::intent::parse::DEFAULT_CANDIDATES;
+use crate::intent::UtteranceStep;
+use crate::output::{JsonOutput, JsonSequenceOutput};
+use crate::Luna;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// File name of the socket inside `$XDG_RUNTIME_DIR`.
+pub const SOCKET_FILE: &str = "luna.sock";
+
+/// JSON-RPC error codes (from the JSON-RPC 2.0 specification).
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Errors from starting the daemon or talking to it.
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error("A Luna daemon is already listening on {0}")]
+    AlreadyRunning(PathBuf),
+
+    #[error("Could not connect to the Luna daemon at {0}: {1} (start it with `luna daemon`)")]
+    NotRunning(PathBuf, io::Error),
+
+    #[error("Invalid response from the daemon: {0}")]
+    InvalidResponse(String),
+
+    #[error("Daemon error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("Socket error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// A JSON-RPC request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl Request {
+    pub fn new(id: u64, method: &str, params: Value) -> Self {
+        Request {
+            jsonrpc: "2.0".to_string(),
+            id: id.into(),
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+/// A JSON-RPC response; exactly one of `result` and `error` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Response {
+    pub fn result(id: Value, result: Value) -> Self {
+        Response {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Response {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(RpcError { code, message: message.into() }),
+        }
+    }
+}
+
+/// The error member of a failed JSON-RPC response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Parameters shared by `parse`, `dry_run` and `execute`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CommandParams {
+    /// The natural language command
+    pub input: String,
+    /// Run actions that need confirmation
+    #[serde(default)]
+    pub yes: bool,
+    /// Keep running a compound command after a step fails
+    #[serde(default)]
+    pub keep_going: bool,
+    /// Number of ranked candidates to include per step
+    #[serde(default = "default_candidates")]
+    pub candidates: usize,
+}
+
+fn default_candidates() -> usize {
+    DEFAULT_CANDIDATES
+}
+
+/// What a request does with its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Parse,
+    DryRun,
+    Execute,
+}
+
+/// The result of a command: one step's output, or a compound command's.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum Reply {
+    Single(Box<JsonOutput>),
+    Sequence(JsonSequenceOutput),
+}
+
+/// Answers requests with one `Luna`, set up at startup.
+///
+/// A handler is shared by every connection. Parsing runs concurrently, but
+/// only one request executes at a time, so two clients can't interleave
+/// the steps of their commands (or race on a read-then-set volume change).
+pub struct Handler {
+    luna: Luna,
+    executing: Mutex<()>,
+}
+
+impl Handler {
+    pub fn new(luna: Luna) -> Self {
+        Handler {
+            luna,
+            executing: Mutex::new(()),
+        }
+    }
+
+    /// Answer one line of input from a client.
+    pub fn handle_line(&self, line: &str) -> Response {
+        match serde_json::from_str::<Value>(line) {
+            Ok(value) => match serde_json::from_value::<Request>(value) {
+                Ok(request) => self.handle(&request),
+                Err(e) => Response::error(Value::Null, INVALID_REQUEST, e.to_string()),
+            },
+            Err(e) => Response::error(Value::Null, PARSE_ERROR, e.to_string()),
+        }
+    }
+
+    /// Answer a request.
+    pub fn handle(&self, request: &Request) -> Response {
+        let id = request.id.clone();
+        if request.jsonrpc != "2.0" {
+            return Response::error(id, INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+        }
+
+        let mode = match request.method.as_str() {
+            "parse" => Mode::Parse,
+            "dry_run" => Mode::DryRun,
+            "execute" => Mode::Execute,
+            method => return Response::error(id, METHOD_NOT_FOUND, format!("Unknown method: {}", method)),
+        };
+        let params = match serde_json::from_value::<CommandParams>(request.params.clone()) {
+            Ok(params) => params,
+            Err(e) => return Response::error(id, INVALID_PARAMS, e.to_string()),
+        };
+
+        match serde_json::to_value(self.run(mode, &params)) {
+            Ok(result) => Response::result(id, result),
+            Err(e) => Response::error(id, INTERNAL_ERROR, e.to_string()),
+        }
+    }
+
+    /// Parse, plan or run `params.input`.
+    pub fn run(&self, mode: Mode, params: &CommandParams) -> Reply {
+        let input = params.input.trim();
+        let steps = match self.luna.parse(input) {
+            Ok(steps) => steps,
+            Err(e) => return Reply::Single(Box::new(JsonOutput::error(input, &e.to_string()))),
+        };
+
+        // Held for the whole command so its steps run back to back
+        let _guard = match mode {
+            Mode::Execute => Some(self.executing.lock().unwrap_or_else(|e| e.into_inner())),
+            _ => None,
+        };
+
+        if let [step] = steps.as_slice() {
+            return Reply::Single(Box::new(self.run_step(mode, params, step)));
+        }
+
+        let mut outputs = Vec::with_capacity(steps.len());
+        let mut failed = false;
+        for step in &steps {
+            if failed && !params.keep_going {
+                outputs.push(JsonOutput::skipped(&step.text, &step.interpretation));
+                continue;
+            }
+            let output = self.run_step(mode, params, step);
+            failed |= !matches!(output.status.as_str(), "success" | "dry-run" | "parsed");
+            outputs.push(output);
+        }
+        Reply::Sequence(JsonSequenceOutput::new(input, outputs))
+    }
+
+    /// Parse, plan or run a single step.
+    fn run_step(&self, mode: Mode, params: &CommandParams, step: &UtteranceStep) -> JsonOutput {
+        let (input, interpretation) = (step.text.as_str(), &step.interpretation);
+        let action = &interpretation.action;
+        let candidates = self.luna.parse_ranked(input, params.candidates).unwrap_or_default();
+
+        let output = match mode {
+            Mode::Parse => JsonOutput::parsed(input, interpretation),
+            Mode::DryRun => JsonOutput::dry_run(input, interpretation, &self.luna.plan(action)),
+            Mode::Execute => match action.confirmation_prompt() {
+                // There's nobody to ask, so these need `"yes": true`
+                Some(prompt) if !params.yes => {
+                    JsonOutput::needs_confirmation(input, interpretation, &self.luna.plan(action), &prompt)
+                }
+                _ => match self.luna.execute(action) {
+                    Ok(result) => JsonOutput::executed(input, interpretation, &result),
+                    Err(e) => return JsonOutput::error(input, &e.to_string()),
+                },
+            },
+        };
+        output.with_candidates(candidates)
+    }
+}
+
+/// The default socket: `$XDG_RUNTIME_DIR/luna.sock`, or a per-user socket in
+/// the temporary directory when there is no runtime directory (macOS).
+pub fn default_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join(SOCKET_FILE),
+        None => {
+            let user = env::var("USER").unwrap_or_else(|_| "user".to_string());
+            env::temp_dir().join(format!("luna-{}.sock", user))
+        }
+    }
+}
+
+/// Bind the daemon's socket, readable and writable by the current user only.
+///
+/// A socket file left behind by a daemon that is no longer running is
+/// replaced; one that still accepts connections is an error.
+pub fn bind(path: &Path) -> Result<UnixListener, DaemonError> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(DaemonError::AlreadyRunning(path.to_path_buf()));
+        }
+        fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Accept connections until the listener fails, serving each client on its
+/// own thread.
+pub fn serve(listener: UnixListener, handler: Arc<Handler>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let handler = Arc::clone(&handler);
+        thread::spawn(move || {
+            if let Err(e) = serve_client(stream, &handler) {
+                eprintln!("Warning: client connection failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answer requests from one client until it disconnects.
+fn serve_client(stream: UnixStream, handler: &Handler) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handler.handle_line(&line);
+        let mut json = serde_json::to_string(&response)?;
+        json.push('\n');
+        writer.write_all(json.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Send one request to the daemon at `path` and return its result.
+pub fn call(path: &Path, method: &str, params: Value) -> Result<Value, DaemonError> {
+    let mut stream = UnixStream::connect(path).map_err(|e| DaemonError::NotRunning(path.to_path_buf(), e))?;
+
+    let mut json = serde_json::to_string(&Request::new(1, method, params))
+        .map_err(|e| DaemonError::InvalidResponse(e.to_string()))?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let response: Response =
+        serde_json::from_str(&line).map_err(|e| DaemonError::InvalidResponse(e.to_string()))?;
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(DaemonError::Rpc { code: error.code, message: error.message }),
+        (Some(result), None) => Ok(result),
+        (None, None) => Err(DaemonError::InvalidResponse("neither result nor error".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::linux::AudioTool;
+    use crate::exec::{CommandOutput, LinuxExecutor, RecordingRunner};
+    use crate::Config;
+    use serde_json::json;
+
+    fn handler() -> (Handler, &'static RecordingRunner) {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(RecordingRunner::new()));
+        let executor = LinuxExecutor::with_runner(runner).with_audio_tool(AudioTool::Wpctl);
+        (Handler::new(Luna::with_executor(Config::default(), Box::new(executor))), runner)
+    }
+
+    fn request(handler: &Handler, method: &str, params: Value) -> Value {
+        let response = handler.handle(&Request::new(7, method, params));
+        assert_eq!(response.id, json!(7));
+        assert_eq!(response.error, None);
+        response.result.unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_dry_run_do_not_execute() {
+        let (handler, runner) = handler();
+
+        let result = request(&handler, "parse", json!({ "input": "open safari" }));
+        assert_eq!(result["status"], "parsed");
+        assert_eq!(result["intent"], "OpenApp(name=\"Safari\")");
+        assert!(result.get("executed").is_none());
+
+        let result = request(&handler, "dry_run", json!({ "input": "set volume to 30" }));
+        assert_eq!(result["status"], "dry-run");
+        assert_eq!(result["executed"], "wpctl set-volume @DEFAULT_AUDIO_SINK@ 30%");
+
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_execute() {
+        let (handler, runner) = handler();
+        runner.push_output(CommandOutput::success(""));
+
+        let result = request(&handler, "execute", json!({ "input": "mute" }));
+        assert_eq!(result["status"], "success");
+        assert_eq!(result["argv"], json!(["wpctl", "set-mute", "@DEFAULT_AUDIO_SINK@", "1"]));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_execute_sequence() {
+        let (handler, runner) = handler();
+        runner.push_output(CommandOutput::failure(1, "no sink"));
+
+        let result = request(&handler, "execute", json!({ "input": "mute and set volume to 30" }));
+        assert_eq!(result["status"], "failed");
+        assert_eq!(result["steps"][0]["status"], "failed");
+        assert_eq!(result["steps"][1]["status"], "skipped");
+        assert_eq!(runner.calls().len(), 1);
+
+        let result = request(&handler, "parse", json!({ "input": "mute and set volume to 30" }));
+        assert_eq!(result["status"], "parsed");
+    }
+
+    #[test]
+    fn test_execute_needs_confirmation() {
+        let (handler, runner) = handler();
+
+        let result = request(&handler, "execute", json!({ "input": "restart" }));
+        assert_eq!(result["status"], "needs_confirmation");
+        assert!(runner.calls().is_empty());
+
+        runner.push_output(CommandOutput::success(""));
+        let result = request(&handler, "execute", json!({ "input": "restart", "yes": true }));
+        assert_eq!(result["status"], "success");
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_unparseable_input_is_a_result() {
+        let (handler, _) = handler();
+        let result = request(&handler, "execute", json!({ "input": "make me a sandwich" }));
+        assert_eq!(result["status"], "error");
+        assert_eq!(result["intent"], "unknown");
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let (handler, _) = handler();
+        let code = |response: Response| response.error.unwrap().code;
+
+        assert_eq!(code(handler.handle_line("{not json")), PARSE_ERROR);
+        assert_eq!(code(handler.handle_line(r#"{"id": 1}"#)), INVALID_REQUEST);
+        assert_eq!(
+            code(handler.handle_line(r#"{"jsonrpc": "1.0", "id": 1, "method": "parse"}"#)),
+            INVALID_REQUEST
+        );
+        assert_eq!(code(handler.handle(&Request::new(1, "reboot", json!({})))), METHOD_NOT_FOUND);
+        assert_eq!(code(handler.handle(&Request::new(1, "parse", json!({ "text": "mute" })))), INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_socket_round_trip_with_concurrent_clients() {
+        let dir = env::temp_dir().join(format!("luna-daemon-test-{}", std::process::id()));
+        let path = dir.join(SOCKET_FILE);
+        let listener = bind(&path).unwrap();
+        assert!(matches!(bind(&path), Err(DaemonError::AlreadyRunning(_))));
+
+        let (handler, _) = handler();
+        thread::spawn(move || serve(listener, Arc::new(handler)));
+
+        let clients: Vec<_> = (0..8)
+            .map(|level| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    call(&path, "dry_run", json!({ "input": format!("set volume to {}", level) })).unwrap()
+                })
+            })
+            .collect();
+        for (level, client) in clients.into_iter().enumerate() {
+            let result = client.join().unwrap();
+            assert_eq!(result["input"], format!("set volume to {}", level));
+            assert_eq!(result["status"], "dry-run");
+        }
+
+        let error = call(&path, "shutdown", json!({})).unwrap_err();
+        assert!(matches!(error, DaemonError::Rpc { code: METHOD_NOT_FOUND, .. }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_call_without_daemon() {
+        let path = env::temp_dir().join("luna-no-such-daemon.sock");
+        assert!(matches!(call(&path, "parse", json!({})), Err(DaemonError::NotRunning(..))));
+    }
+}
@@ -0,0 +1,212 @@
+//! Clipboard support for Luna.
+//!
+//! Each backend supplies a "paste" plan that prints the clipboard and a
+//! "copy" plan that reads new contents from stdin; reading, writing and
+//! transforming the clipboard are built from those two here.
+
+use super::{run_plan, CommandPlan, CommandRunner, ExecError, ExecResult};
+use crate::intent::TextTransform;
+
+/// Characters that carry no visible text: zero-width spaces and joiners,
+/// the byte order mark and soft hyphens.
+const INVISIBLE_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}', '\u{00AD}'];
+
+/// Apply a transform to clipboard text.
+pub fn apply_transform(transform: TextTransform, text: &str) -> String {
+    match transform {
+        TextTransform::Lowercase => text.to_lowercase(),
+        TextTransform::Uppercase => text.to_uppercase(),
+        TextTransform::TitleCase => title_case(text),
+        TextTransform::Trim => text.trim().to_string(),
+        TextTransform::StripFormatting => strip_formatting(text),
+        TextTransform::UrlDecode => url_decode(text),
+    }
+}
+
+/// Capitalize the first letter of each word and lowercase the rest,
+/// keeping the original spacing.
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if at_word_start {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        at_word_start = c.is_whitespace();
+    }
+    result
+}
+
+/// Plain text only: non-breaking spaces become spaces, invisible characters
+/// are dropped and trailing whitespace is trimmed from each line.
+///
+/// Rich text formatting is lost anyway once the text is copied back as
+/// plain text.
+fn strip_formatting(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !INVISIBLE_CHARS.contains(c))
+                .map(|c| if c == '\u{00A0}' { ' ' } else { c })
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decode `%XX` escapes; malformed escapes are kept as written and invalid
+/// UTF-8 is replaced.
+pub fn url_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Run a copy plan and record `text` as the new clipboard contents.
+pub(crate) fn run_copy<R: CommandRunner + ?Sized>(
+    runner: &R,
+    plan: CommandPlan,
+    text: &str,
+) -> Result<ExecResult, ExecError> {
+    let mut result = run_plan(runner, plan)?;
+    if result.success {
+        result.clipboard = Some(text.to_string());
+    }
+    Ok(result)
+}
+
+/// Run a paste plan and report the clipboard contents.
+pub(crate) fn run_read_clipboard<R: CommandRunner + ?Sized>(
+    runner: &R,
+    paste: CommandPlan,
+) -> Result<ExecResult, ExecError> {
+    let output = runner.run(&paste)?;
+    if !output.success {
+        return Ok(ExecResult {
+            command: paste,
+            success: false,
+            output: Some(output.stderr),
+            now_playing: None,
+            clipboard: None,
+        });
+    }
+
+    Ok(ExecResult {
+        command: paste,
+        success: true,
+        output: Some(output.stdout.clone()),
+        now_playing: None,
+        clipboard: Some(output.stdout),
+    })
+}
+
+/// Read the clipboard with `paste`, then copy back the transformed text
+/// with the plan `copy` builds.
+///
+/// If reading fails its result is returned as-is.
+pub(crate) fn run_transform_clipboard<R: CommandRunner + ?Sized>(
+    runner: &R,
+    paste: CommandPlan,
+    transform: TextTransform,
+    copy: impl FnOnce(&str) -> CommandPlan,
+) -> Result<ExecResult, ExecError> {
+    let read = run_read_clipboard(runner, paste)?;
+    let transformed = match read.clipboard {
+        Some(ref text) if read.success => apply_transform(transform, text),
+        _ => return Ok(read),
+    };
+
+    let mut result = run_copy(runner, copy(&transformed), &transformed)?;
+    if result.success {
+        result.output = Some(transformed);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{CommandOutput, RecordingRunner};
+
+    #[test]
+    fn test_apply_transform() {
+        let text = "  hello WORLD\tagain ";
+        assert_eq!(apply_transform(TextTransform::Lowercase, text), "  hello world\tagain ");
+        assert_eq!(apply_transform(TextTransform::Uppercase, text), "  HELLO WORLD\tAGAIN ");
+        assert_eq!(apply_transform(TextTransform::TitleCase, text), "  Hello World\tAgain ");
+        assert_eq!(apply_transform(TextTransform::Trim, text), "hello WORLD\tagain");
+    }
+
+    #[test]
+    fn test_strip_formatting() {
+        assert_eq!(
+            apply_transform(TextTransform::StripFormatting, "price:\u{00A0}10\u{200B}€  \nnext\u{00AD}line"),
+            "price: 10€\nnextline"
+        );
+    }
+
+    #[test]
+    fn test_url_decode() {
+        assert_eq!(url_decode("rust%20lifetimes%3F"), "rust lifetimes?");
+        assert_eq!(url_decode("caf%C3%A9"), "café");
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(url_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn test_run_transform_clipboard() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("Hello World"));
+        let result = run_transform_clipboard(
+            &runner,
+            CommandPlan::new("paste"),
+            TextTransform::Lowercase,
+            |text| CommandPlan::new("copy").stdin(text),
+        )
+        .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.clipboard.as_deref(), Some("hello world"));
+        assert_eq!(result.command, CommandPlan::new("copy").stdin("hello world"));
+        assert_eq!(runner.calls().len(), 2);
+    }
+
+    #[test]
+    fn test_run_transform_clipboard_read_failure() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::failure(1, "Error: target STRING not available"));
+        let result = run_transform_clipboard(
+            &runner,
+            CommandPlan::new("paste"),
+            TextTransform::Trim,
+            |text| CommandPlan::new("copy").stdin(text),
+        )
+        .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.clipboard, None);
+        assert_eq!(runner.calls().len(), 1);
+    }
+}
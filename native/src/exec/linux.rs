@@ -0,0 +1,820 @@
+//! Linux execution layer for Luna.
+//!
+//! Launches apps with `gtk-launch` (by desktop ID from the app catalog), opens URLs with `xdg-open`, drives
+//! audio through whichever mixer is installed (`pactl`, `wpctl` or `amixer`) and sets screen brightness with
+//! `brightnessctl` or the kernel's backlight interface. The clipboard goes through `wl-copy`/`wl-paste` on
+//! Wayland and `xclip` on X11.
+
+use super::clipboard::{run_copy, run_read_clipboard, run_transform_clipboard};
+use super::media::run_now_playing;
+use super::{is_on_path, run_plan, run_level_adjust, CommandPlan, CommandRunner, ExecError, ExecResult, Executor, SystemRunner};
+use crate::catalog::AppCatalog;
+use crate::config::search::percent_encode;
+use crate::config::SearchEngines;
+use crate::intent::Action;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory the kernel lists backlight devices in.
+const SYSFS_BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+/// Audio control tool used for volume and mute actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioTool {
+    /// PulseAudio / PipeWire-pulse
+    Pactl,
+    /// WirePlumber (PipeWire)
+    Wpctl,
+    /// ALSA mixer
+    Amixer,
+}
+
+impl AudioTool {
+    /// Detect the first available audio tool on `PATH`.
+    ///
+    /// Falls back to `pactl` when nothing is found so that dry-run output
+    /// still shows a sensible command.
+    pub fn detect() -> Self {
+        [AudioTool::Pactl, AudioTool::Wpctl, AudioTool::Amixer]
+            .into_iter()
+            .find(|tool| is_on_path(tool.program()))
+            .unwrap_or(AudioTool::Pactl)
+    }
+
+    /// Name of the executable for this tool.
+    pub fn program(self) -> &'static str {
+        match self {
+            AudioTool::Pactl => "pactl",
+            AudioTool::Wpctl => "wpctl",
+            AudioTool::Amixer => "amixer",
+        }
+    }
+
+    /// Arguments that set the output volume to `level` percent.
+    fn volume_args(self, level: u8) -> Vec<String> {
+        let percent = format!("{}%", level);
+        match self {
+            AudioTool::Pactl => vec!["set-sink-volume".into(), "@DEFAULT_SINK@".into(), percent],
+            AudioTool::Wpctl => vec!["set-volume".into(), "@DEFAULT_AUDIO_SINK@".into(), percent],
+            AudioTool::Amixer => vec!["-q".into(), "sset".into(), "Master".into(), percent],
+        }
+    }
+
+    /// Arguments that print the current output volume.
+    fn query_args(self) -> Vec<String> {
+        match self {
+            AudioTool::Pactl => vec!["get-sink-volume".into(), "@DEFAULT_SINK@".into()],
+            AudioTool::Wpctl => vec!["get-volume".into(), "@DEFAULT_AUDIO_SINK@".into()],
+            AudioTool::Amixer => vec!["sget".into(), "Master".into()],
+        }
+    }
+
+    /// Parser for the output of `query_args`.
+    fn level_parser(self) -> fn(&str) -> Option<u8> {
+        match self {
+            AudioTool::Pactl | AudioTool::Amixer => parse_percent_level,
+            AudioTool::Wpctl => parse_wpctl_level,
+        }
+    }
+
+    /// Arguments that mute or unmute the default output.
+    fn mute_args(self, muted: bool) -> Vec<String> {
+        let flag = if muted { "1" } else { "0" };
+        match self {
+            AudioTool::Pactl => vec!["set-sink-mute".into(), "@DEFAULT_SINK@".into(), flag.into()],
+            AudioTool::Wpctl => vec!["set-mute".into(), "@DEFAULT_AUDIO_SINK@".into(), flag.into()],
+            AudioTool::Amixer => vec![
+                "-q".into(),
+                "sset".into(),
+                "Master".into(),
+                if muted { "mute" } else { "unmute" }.into(),
+            ],
+        }
+    }
+}
+
+/// Clipboard tool used for clipboard actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardTool {
+    /// `wl-copy` / `wl-paste` (Wayland)
+    WlClipboard,
+    /// `xclip` (X11)
+    Xclip,
+}
+
+impl ClipboardTool {
+    /// Use `wl-clipboard` in a Wayland session and `xclip` otherwise, unless
+    /// only the other one is installed.
+    pub fn detect() -> Self {
+        let wayland = env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty());
+        let preferred = if wayland { ClipboardTool::WlClipboard } else { ClipboardTool::Xclip };
+        let other = if wayland { ClipboardTool::Xclip } else { ClipboardTool::WlClipboard };
+        if !is_on_path(preferred.copy_program()) && is_on_path(other.copy_program()) {
+            other
+        } else {
+            preferred
+        }
+    }
+
+    /// Name of the executable that writes the clipboard.
+    fn copy_program(self) -> &'static str {
+        match self {
+            ClipboardTool::WlClipboard => "wl-copy",
+            ClipboardTool::Xclip => "xclip",
+        }
+    }
+
+    /// Plan that puts `text` on the clipboard.
+    ///
+    /// Both tools leave a process behind to serve the selection, so their
+    /// output isn't captured.
+    fn copy_plan(self, text: &str) -> CommandPlan {
+        let plan = match self {
+            ClipboardTool::WlClipboard => CommandPlan::new("wl-copy"),
+            ClipboardTool::Xclip => CommandPlan::new("xclip").args(["-selection", "clipboard"]),
+        };
+        plan.stdin(text).discard_output()
+    }
+
+    /// Plan that prints the clipboard.
+    fn paste_plan(self) -> CommandPlan {
+        match self {
+            ClipboardTool::WlClipboard => CommandPlan::new("wl-paste").arg("--no-newline"),
+            ClipboardTool::Xclip => CommandPlan::new("xclip").args(["-selection", "clipboard", "-o"]),
+        }
+    }
+}
+
+/// Backlight control used for brightness actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BacklightTool {
+    /// `brightnessctl`, which works without root through logind
+    Brightnessctl,
+    /// A `/sys/class/backlight` device, written directly (needs write access)
+    Sysfs {
+        /// Device directory ("/sys/class/backlight/intel_backlight")
+        device: PathBuf,
+        /// Raw value of `max_brightness`
+        max: u32,
+    },
+}
+
+impl BacklightTool {
+    /// Use `brightnessctl` if it is on `PATH`, otherwise the first sysfs
+    /// backlight device.
+    ///
+    /// Falls back to `brightnessctl` when neither is found so that dry-run
+    /// output still shows a sensible command.
+    pub fn detect() -> Self {
+        if is_on_path("brightnessctl") {
+            return BacklightTool::Brightnessctl;
+        }
+        Self::first_sysfs_device(Path::new(SYSFS_BACKLIGHT_DIR)).unwrap_or(BacklightTool::Brightnessctl)
+    }
+
+    /// The first device under `dir` with a readable `max_brightness`, by name.
+    pub fn first_sysfs_device(dir: &Path) -> Option<Self> {
+        let mut devices: Vec<PathBuf> = fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        devices.sort();
+
+        devices.into_iter().find_map(|device| {
+            let max = fs::read_to_string(device.join("max_brightness")).ok()?.trim().parse().ok()?;
+            (max > 0).then_some(BacklightTool::Sysfs { device, max })
+        })
+    }
+
+    /// Plan that sets the brightness to `level` percent.
+    fn set_plan(&self, level: u8) -> CommandPlan {
+        match self {
+            BacklightTool::Brightnessctl => CommandPlan::new("brightnessctl").arg("set").arg(format!("{}%", level)),
+            // printf '%s' <raw> | tee <device>/brightness
+            BacklightTool::Sysfs { device, max } => {
+                let raw = (u64::from(level) * u64::from(*max) + 50) / 100;
+                CommandPlan::new("tee")
+                    .arg(device.join("brightness").to_string_lossy())
+                    .stdin(raw.to_string())
+            }
+        }
+    }
+
+    /// Plan for a relative change. `brightnessctl` applies it in one step;
+    /// for sysfs this is the query for the current level.
+    fn adjust_plan(&self, delta: i8) -> CommandPlan {
+        match self {
+            BacklightTool::Brightnessctl => {
+                let sign = if delta < 0 { '-' } else { '+' };
+                CommandPlan::new("brightnessctl")
+                    .arg("set")
+                    .arg(format!("{}%{}", delta.unsigned_abs(), sign))
+            }
+            BacklightTool::Sysfs { device, .. } => {
+                CommandPlan::new("cat").arg(device.join("brightness").to_string_lossy())
+            }
+        }
+    }
+
+    /// Convert the raw sysfs `brightness` value to a percentage.
+    fn parse_sysfs_level(output: &str, max: u32) -> Option<u8> {
+        let raw: u64 = output.trim().parse().ok()?;
+        let max = u64::from(max);
+        Some(((raw * 100 + max / 2) / max).min(100) as u8)
+    }
+}
+
+/// Executor for Linux.
+#[derive(Debug)]
+pub struct LinuxExecutor<R: CommandRunner = SystemRunner> {
+    runner: R,
+    audio: AudioTool,
+    backlight: BacklightTool,
+    clipboard: ClipboardTool,
+    catalog: AppCatalog,
+    search: SearchEngines,
+}
+
+impl LinuxExecutor {
+    /// Create an executor that runs real processes, using the detected audio
+    /// tool, the installed app catalog and the configured search engines.
+    pub fn new() -> Self {
+        Self::with_runner(SystemRunner)
+            .with_catalog(AppCatalog::load_default())
+            .with_search_engines(SearchEngines::load_default().unwrap_or_default())
+    }
+}
+
+impl Default for LinuxExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: CommandRunner> LinuxExecutor<R> {
+    /// Create an executor that runs commands through `runner`, using the
+    /// detected audio, backlight and clipboard tools.
+    pub fn with_runner(runner: R) -> Self {
+        Self {
+            runner,
+            audio: AudioTool::detect(),
+            backlight: BacklightTool::detect(),
+            clipboard: ClipboardTool::detect(),
+            catalog: AppCatalog::default(),
+            search: SearchEngines::default(),
+        }
+    }
+
+    /// Use a specific audio tool instead of the detected one.
+    pub fn with_audio_tool(mut self, audio: AudioTool) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    /// Use a specific backlight tool instead of the detected one.
+    pub fn with_backlight_tool(mut self, backlight: BacklightTool) -> Self {
+        self.backlight = backlight;
+        self
+    }
+
+    /// Use a specific clipboard tool instead of the detected one.
+    pub fn with_clipboard_tool(mut self, clipboard: ClipboardTool) -> Self {
+        self.clipboard = clipboard;
+        self
+    }
+
+    /// Launch apps by the desktop IDs in `catalog` instead of guessing them from names.
+    pub fn with_catalog(mut self, catalog: AppCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Build web search URLs from `search` instead of the built-in engines.
+    pub fn with_search_engines(mut self, search: SearchEngines) -> Self {
+        self.search = search;
+        self
+    }
+}
+
+impl<R: CommandRunner> LinuxExecutor<R> {
+    /// The program an app runs, from the catalog or guessed from its name.
+    fn program(&self, name: &str) -> String {
+        self.catalog
+            .get(name)
+            .and_then(|app| app.exec.clone())
+            .unwrap_or_else(|| desktop_id(name))
+    }
+
+    /// The process name `pkill -x` matches, which the kernel truncates to
+    /// 15 bytes.
+    fn process_name(&self, name: &str) -> String {
+        let mut program = self.program(name);
+        let mut end = program.len().min(15);
+        while !program.is_char_boundary(end) {
+            end -= 1;
+        }
+        program.truncate(end);
+        program
+    }
+}
+
+impl<R: CommandRunner> Executor for LinuxExecutor<R> {
+    fn plan(&self, action: &Action) -> CommandPlan {
+        let tool = self.audio;
+        match action {
+            Action::OpenApp { name } => {
+                let id = self.catalog.get(name).map_or_else(|| desktop_id(name), |app| app.id.clone());
+                CommandPlan::new("gtk-launch").arg(id)
+            }
+            // SIGTERM lets the app save and exit; SIGKILL doesn't
+            Action::QuitApp { name } => pkill("-TERM", &self.process_name(name)),
+            Action::ForceQuitApp { name } => pkill("-KILL", &self.process_name(name)),
+            Action::HideApp { name } => xdotool_windows(&self.program(name), &["windowminimize", "%@"]),
+            Action::FocusApp { name } => xdotool_windows(&self.program(name), &["windowactivate"]),
+            Action::OpenUrl { url } => CommandPlan::new("xdg-open").arg(url),
+            Action::OpenPath { path, reveal: false } => CommandPlan::new("xdg-open").arg(path),
+            Action::OpenPath { path, reveal: true } => show_in_file_manager(path),
+            Action::WebSearch { query, engine } => self.plan(&Action::OpenUrl {
+                url: self.search.url(engine, query),
+            }),
+            Action::SetVolume { level } => {
+                CommandPlan::new(tool.program()).args(tool.volume_args(*level))
+            }
+            Action::AdjustVolume { .. } => CommandPlan::new(tool.program()).args(tool.query_args()),
+            Action::Mute => CommandPlan::new(tool.program()).args(tool.mute_args(true)),
+            Action::Unmute => CommandPlan::new(tool.program()).args(tool.mute_args(false)),
+            Action::SetBrightness { level } => self.backlight.set_plan(*level),
+            Action::AdjustBrightness { delta } => self.backlight.adjust_plan(*delta),
+            // systemd-logind handles power and session requests without root
+            Action::LockScreen => CommandPlan::new("loginctl").arg("lock-session"),
+            Action::Sleep => CommandPlan::new("systemctl").arg("suspend"),
+            Action::Restart => CommandPlan::new("systemctl").arg("reboot"),
+            Action::Shutdown => CommandPlan::new("systemctl").arg("poweroff"),
+            // "auto" is the caller's session, or the user's graphical one
+            Action::LogOut => CommandPlan::new("loginctl").args(["terminate-session", "auto"]),
+            Action::CopyText { text } => self.clipboard.copy_plan(text),
+            Action::ReadClipboard | Action::TransformClipboard { .. } => self.clipboard.paste_plan(),
+            // The body may be read as Pango markup; "--" keeps a leading "-" from being an option
+            Action::Notify { title, body } => CommandPlan::new("notify-send")
+                .arg("--app-name=Luna")
+                .arg("--")
+                .arg(title)
+                .arg(escape_markup(body)),
+            // playerctl talks to any MPRIS player (Spotify, VLC, browsers, ...)
+            Action::Play => CommandPlan::new("playerctl").arg("play"),
+            Action::Pause => CommandPlan::new("playerctl").arg("pause"),
+            Action::TogglePlayback => CommandPlan::new("playerctl").arg("play-pause"),
+            Action::NextTrack => CommandPlan::new("playerctl").arg("next"),
+            Action::PreviousTrack => CommandPlan::new("playerctl").arg("previous"),
+            Action::NowPlaying => CommandPlan::new("playerctl")
+                .args(["metadata", "--format", NOW_PLAYING_FORMAT]),
+        }
+    }
+
+    fn execute(&self, action: &Action) -> Result<ExecResult, ExecError> {
+        match action {
+            Action::AdjustVolume { delta } => run_level_adjust(
+                &self.runner,
+                self.plan(action),
+                self.audio.level_parser(),
+                *delta,
+                |level| self.plan(&Action::SetVolume { level }),
+            ),
+            Action::AdjustBrightness { delta } => match self.backlight {
+                BacklightTool::Sysfs { max, .. } => run_level_adjust(
+                    &self.runner,
+                    self.plan(action),
+                    |output| BacklightTool::parse_sysfs_level(output, max),
+                    *delta,
+                    |level| self.backlight.set_plan(level),
+                ),
+                BacklightTool::Brightnessctl => run_plan(&self.runner, self.plan(action)),
+            },
+            Action::NowPlaying => run_now_playing(&self.runner, self.plan(action)),
+            Action::CopyText { text } => run_copy(&self.runner, self.plan(action), text),
+            Action::ReadClipboard => run_read_clipboard(&self.runner, self.plan(action)),
+            Action::TransformClipboard { transform } => run_transform_clipboard(
+                &self.runner,
+                self.plan(action),
+                *transform,
+                |text| self.clipboard.copy_plan(text),
+            ),
+            _ => run_plan(&self.runner, self.plan(action)),
+        }
+    }
+}
+
+/// `playerctl metadata` template for the "player\ttitle\tartist\talbum" line.
+const NOW_PLAYING_FORMAT: &str = "{{playerName}}\t{{title}}\t{{artist}}\t{{album}}";
+
+/// Parse the first percentage in mixer output.
+///
+/// Handles `pactl` ("Volume: front-left: 26214 /  40% / ...") and
+/// `amixer` ("Front Left: Playback 26214 [40%] [on]").
+fn parse_percent_level(output: &str) -> Option<u8> {
+    let end = output.find('%')?;
+    let start = output[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    output[start..end].parse::<u16>().ok().map(|level| level.min(100) as u8)
+}
+
+/// Parse `wpctl get-volume` output ("Volume: 0.40" or "Volume: 0.40 [MUTED]").
+fn parse_wpctl_level(output: &str) -> Option<u8> {
+    let value: f32 = output.split_whitespace().nth(1)?.parse().ok()?;
+    Some((value * 100.0).round().clamp(0.0, 100.0) as u8)
+}
+
+/// Plan: pkill <signal> -i -x <process>
+fn pkill(signal: &str, process: &str) -> CommandPlan {
+    CommandPlan::new("pkill").args([signal, "-i", "-x", process])
+}
+
+/// Plan: run an `xdotool` window command on the visible windows of an app,
+/// matched by window class (X11 only).
+fn xdotool_windows(class: &str, command: &[&str]) -> CommandPlan {
+    CommandPlan::new("xdotool")
+        .args(["search", "--onlyvisible", "--class", class])
+        .args(command.iter().copied())
+}
+
+/// Plan: ask the file manager to select `path`, through the freedesktop
+/// `FileManager1` D-Bus interface (Nautilus, Dolphin, Nemo, ...).
+fn show_in_file_manager(path: &str) -> CommandPlan {
+    CommandPlan::new("dbus-send")
+        .args([
+            "--session",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ])
+        .arg(format!("array:string:{}", file_uri(path)))
+        .arg("string:")
+}
+
+/// Escape the characters that notification servers treat as markup.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// A `file://` URI for an absolute path, percent-encoding each component.
+fn file_uri(path: &str) -> String {
+    let encoded: Vec<String> = path.split('/').map(percent_encode).collect();
+    format!("file://{}", encoded.join("/"))
+}
+
+/// Guess the desktop entry ID of an app missing from the catalog ("Visual Studio Code" -> "visual-studio-code").
+fn desktop_id(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::AppEntry;
+    use crate::exec::{CommandOutput, RecordingRunner};
+    use crate::intent::TextTransform;
+
+    fn command_string_with(action: &Action, tool: AudioTool) -> String {
+        LinuxExecutor::with_runner(RecordingRunner::new())
+            .with_audio_tool(tool)
+            .plan(action)
+            .to_string()
+    }
+
+    #[test]
+    fn test_command_string_open_app() {
+        let action = Action::OpenApp { name: "Visual Studio Code".to_string() };
+        assert_eq!(
+            command_string_with(&action, AudioTool::Pactl),
+            "gtk-launch visual-studio-code"
+        );
+    }
+
+    #[test]
+    fn test_command_string_open_app_from_catalog() {
+        let catalog = AppCatalog {
+            dirs: Vec::new(),
+            apps: vec![AppEntry {
+                name: "Visual Studio Code".to_string(),
+                id: "code".to_string(),
+                path: "/usr/share/applications/code.desktop".into(),
+                exec: Some("code".to_string()),
+            }],
+        };
+        let executor = LinuxExecutor::with_runner(RecordingRunner::new()).with_catalog(catalog);
+        let action = Action::OpenApp { name: "Visual Studio Code".to_string() };
+        assert_eq!(executor.plan(&action).to_string(), "gtk-launch code");
+
+        let action = Action::QuitApp { name: "Visual Studio Code".to_string() };
+        assert_eq!(executor.plan(&action).to_string(), "pkill -TERM -i -x code");
+    }
+
+    fn sysfs_backlight() -> BacklightTool {
+        BacklightTool::Sysfs { device: "/sys/class/backlight/intel_backlight".into(), max: 1200 }
+    }
+
+    #[test]
+    fn test_command_string_brightness() {
+        let plan = |action: Action, backlight: BacklightTool| {
+            LinuxExecutor::with_runner(RecordingRunner::new())
+                .with_backlight_tool(backlight)
+                .plan(&action)
+                .to_string()
+        };
+        assert_eq!(
+            plan(Action::SetBrightness { level: 40 }, BacklightTool::Brightnessctl),
+            "brightnessctl set 40%"
+        );
+        assert_eq!(
+            plan(Action::AdjustBrightness { delta: -10 }, BacklightTool::Brightnessctl),
+            "brightnessctl set 10%-"
+        );
+        assert_eq!(
+            plan(Action::AdjustBrightness { delta: 5 }, BacklightTool::Brightnessctl),
+            "brightnessctl set 5%+"
+        );
+        assert_eq!(
+            plan(Action::SetBrightness { level: 40 }, sysfs_backlight()),
+            "printf '%s' 480 | tee /sys/class/backlight/intel_backlight/brightness"
+        );
+    }
+
+    #[test]
+    fn test_execute_adjust_brightness_sysfs() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("600\n"));
+        let executor = LinuxExecutor::with_runner(&runner).with_backlight_tool(sysfs_backlight());
+        let result = executor.execute(&Action::AdjustBrightness { delta: 20 }).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.command.stdin.as_deref(), Some("840"));
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].to_string(), "cat /sys/class/backlight/intel_backlight/brightness");
+    }
+
+    #[test]
+    fn test_first_sysfs_device() {
+        let dir = env::temp_dir().join(format!("luna-backlight-{}", std::process::id()));
+        fs::create_dir_all(dir.join("acpi_video0")).unwrap();
+        fs::create_dir_all(dir.join("intel_backlight")).unwrap();
+        fs::write(dir.join("intel_backlight").join("max_brightness"), "19200\n").unwrap();
+
+        assert_eq!(
+            BacklightTool::first_sysfs_device(&dir),
+            Some(BacklightTool::Sysfs { device: dir.join("intel_backlight"), max: 19200 })
+        );
+        assert_eq!(BacklightTool::first_sysfs_device(&dir.join("missing")), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_sysfs_level() {
+        assert_eq!(BacklightTool::parse_sysfs_level("600\n", 1200), Some(50));
+        assert_eq!(BacklightTool::parse_sysfs_level("1200", 1200), Some(100));
+        assert_eq!(BacklightTool::parse_sysfs_level("n/a", 1200), None);
+    }
+
+    #[test]
+    fn test_command_string_clipboard() {
+        let plan = |action: Action, clipboard: ClipboardTool| {
+            LinuxExecutor::with_runner(RecordingRunner::new())
+                .with_clipboard_tool(clipboard)
+                .plan(&action)
+                .to_string()
+        };
+        let copy = || Action::CopyText { text: "me@example.com".to_string() };
+        assert_eq!(
+            plan(copy(), ClipboardTool::WlClipboard),
+            "printf '%s' me@example.com | wl-copy >/dev/null 2>&1"
+        );
+        assert_eq!(
+            plan(copy(), ClipboardTool::Xclip),
+            "printf '%s' me@example.com | xclip -selection clipboard >/dev/null 2>&1"
+        );
+        assert_eq!(plan(Action::ReadClipboard, ClipboardTool::WlClipboard), "wl-paste --no-newline");
+        assert_eq!(plan(Action::ReadClipboard, ClipboardTool::Xclip), "xclip -selection clipboard -o");
+    }
+
+    #[test]
+    fn test_execute_transform_clipboard() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("https%3A%2F%2Fexample.com"));
+        let executor = LinuxExecutor::with_runner(&runner).with_clipboard_tool(ClipboardTool::Xclip);
+        let result = executor
+            .execute(&Action::TransformClipboard { transform: TextTransform::UrlDecode })
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.clipboard.as_deref(), Some("https://example.com"));
+        let calls = runner.calls();
+        assert_eq!(calls[0].to_string(), "xclip -selection clipboard -o");
+        assert_eq!(calls[1].stdin.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn test_command_string_notify() {
+        let action = Action::Notify { title: "Luna".to_string(), body: "-v <b>tea</b> & cake".to_string() };
+        assert_eq!(
+            LinuxExecutor::with_runner(RecordingRunner::new()).plan(&action).argv(),
+            vec!["notify-send", "--app-name=Luna", "--", "Luna", "-v &lt;b&gt;tea&lt;/b&gt; &amp; cake"]
+        );
+    }
+
+    #[test]
+    fn test_command_string_power() {
+        let plan = |action: Action| command_string_with(&action, AudioTool::Pactl);
+        assert_eq!(plan(Action::LockScreen), "loginctl lock-session");
+        assert_eq!(plan(Action::Sleep), "systemctl suspend");
+        assert_eq!(plan(Action::Restart), "systemctl reboot");
+        assert_eq!(plan(Action::Shutdown), "systemctl poweroff");
+        assert_eq!(plan(Action::LogOut), "loginctl terminate-session auto");
+    }
+
+    #[test]
+    fn test_command_string_media() {
+        let plan = |action: Action| command_string_with(&action, AudioTool::Pactl);
+        assert_eq!(plan(Action::Play), "playerctl play");
+        assert_eq!(plan(Action::Pause), "playerctl pause");
+        assert_eq!(plan(Action::TogglePlayback), "playerctl play-pause");
+        assert_eq!(plan(Action::NextTrack), "playerctl next");
+        assert_eq!(plan(Action::PreviousTrack), "playerctl previous");
+    }
+
+    #[test]
+    fn test_execute_now_playing() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("spotify\tSo What\tMiles Davis\tKind of Blue\n"));
+        let executor = LinuxExecutor::with_runner(&runner);
+        let result = executor.execute(&Action::NowPlaying).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.now_playing.unwrap().title, "So What");
+        assert_eq!(runner.calls()[0].args[..2], ["metadata", "--format"]);
+    }
+
+    #[test]
+    fn test_command_string_app_control() {
+        let name = || "Slack".to_string();
+        let plan = |action: Action| command_string_with(&action, AudioTool::Pactl);
+        assert_eq!(plan(Action::QuitApp { name: name() }), "pkill -TERM -i -x slack");
+        assert_eq!(plan(Action::ForceQuitApp { name: name() }), "pkill -KILL -i -x slack");
+        assert_eq!(
+            plan(Action::HideApp { name: name() }),
+            "xdotool search --onlyvisible --class slack windowminimize %@"
+        );
+        assert_eq!(
+            plan(Action::FocusApp { name: name() }),
+            "xdotool search --onlyvisible --class slack windowactivate"
+        );
+        assert_eq!(
+            plan(Action::QuitApp { name: "Some Very Long Application".to_string() }),
+            "pkill -TERM -i -x some-very-long-"
+        );
+    }
+
+    #[test]
+    fn test_command_string_open_url() {
+        let action = Action::OpenUrl { url: "https://google.com".to_string() };
+        assert_eq!(
+            command_string_with(&action, AudioTool::Pactl),
+            "xdg-open https://google.com"
+        );
+    }
+
+    #[test]
+    fn test_command_string_open_path() {
+        let open = Action::OpenPath { path: "/home/me/Downloads".to_string(), reveal: false };
+        assert_eq!(command_string_with(&open, AudioTool::Pactl), "xdg-open /home/me/Downloads");
+
+        let reveal = Action::OpenPath { path: "/home/me/My Notes/todo.md".to_string(), reveal: true };
+        assert_eq!(
+            LinuxExecutor::with_runner(RecordingRunner::new()).plan(&reveal).argv()[6],
+            "array:string:file:///home/me/My%20Notes/todo.md"
+        );
+    }
+
+    #[test]
+    fn test_command_string_web_search() {
+        let action = Action::WebSearch { query: "rust lifetimes".to_string(), engine: "google".to_string() };
+        assert_eq!(
+            command_string_with(&action, AudioTool::Pactl),
+            "xdg-open 'https://www.google.com/search?q=rust%20lifetimes'"
+        );
+    }
+
+    #[test]
+    fn test_command_string_set_volume() {
+        let action = Action::SetVolume { level: 40 };
+        assert_eq!(
+            command_string_with(&action, AudioTool::Pactl),
+            "pactl set-sink-volume @DEFAULT_SINK@ 40%"
+        );
+        assert_eq!(
+            command_string_with(&action, AudioTool::Wpctl),
+            "wpctl set-volume @DEFAULT_AUDIO_SINK@ 40%"
+        );
+        assert_eq!(
+            command_string_with(&action, AudioTool::Amixer),
+            "amixer -q sset Master 40%"
+        );
+    }
+
+    #[test]
+    fn test_command_string_mute_unmute() {
+        assert_eq!(
+            command_string_with(&Action::Mute, AudioTool::Pactl),
+            "pactl set-sink-mute @DEFAULT_SINK@ 1"
+        );
+        assert_eq!(
+            command_string_with(&Action::Unmute, AudioTool::Wpctl),
+            "wpctl set-mute @DEFAULT_AUDIO_SINK@ 0"
+        );
+        assert_eq!(
+            command_string_with(&Action::Mute, AudioTool::Amixer),
+            "amixer -q sset Master mute"
+        );
+    }
+
+    #[test]
+    fn test_execute_uses_runner() {
+        let runner = RecordingRunner::new();
+        let executor = LinuxExecutor::with_runner(&runner).with_audio_tool(AudioTool::Wpctl);
+        let result = executor.execute(&Action::SetVolume { level: 25 }).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.command.to_string(), "wpctl set-volume @DEFAULT_AUDIO_SINK@ 25%");
+        assert_eq!(
+            runner.calls(),
+            vec![CommandPlan::new("wpctl").args(["set-volume", "@DEFAULT_AUDIO_SINK@", "25%"])]
+        );
+    }
+
+    #[test]
+    fn test_execute_failure_fills_output() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::failure(1, "gtk-launch: no such application nope"));
+        let executor = LinuxExecutor::with_runner(&runner);
+        let result = executor
+            .execute(&Action::OpenApp { name: "Nope".to_string() })
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.command.to_string(), "gtk-launch nope");
+        assert_eq!(
+            result.output.as_deref(),
+            Some("gtk-launch: no such application nope")
+        );
+    }
+
+    #[test]
+    fn test_parse_levels() {
+        assert_eq!(
+            parse_percent_level(
+                "Volume: front-left: 26214 /  40% / -23.88 dB,   front-right: 26214 /  40% / -23.88 dB"
+            ),
+            Some(40)
+        );
+        assert_eq!(
+            parse_percent_level("  Front Left: Playback 45 [70%] [-18.00dB] [on]"),
+            Some(70)
+        );
+        assert_eq!(parse_wpctl_level("Volume: 0.35 [MUTED]\n"), Some(35));
+        assert_eq!(parse_percent_level("no volume here"), None);
+    }
+
+    #[test]
+    fn test_execute_adjust_volume() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("Volume: 0.08\n"));
+        let executor = LinuxExecutor::with_runner(&runner).with_audio_tool(AudioTool::Wpctl);
+        let result = executor.execute(&Action::AdjustVolume { delta: -10 }).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.command.to_string(), "wpctl set-volume @DEFAULT_AUDIO_SINK@ 0%");
+        assert_eq!(
+            runner.calls()[0],
+            CommandPlan::new("wpctl").args(["get-volume", "@DEFAULT_AUDIO_SINK@"])
+        );
+    }
+
+    #[test]
+    fn test_execute_adjust_volume_query_failure() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::failure(1, "Connection failure"));
+        let executor = LinuxExecutor::with_runner(&runner).with_audio_tool(AudioTool::Pactl);
+        let result = executor.execute(&Action::AdjustVolume { delta: 10 }).unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.command.to_string(), "pactl get-sink-volume @DEFAULT_SINK@");
+        assert_eq!(result.output.as_deref(), Some("Connection failure"));
+        assert_eq!(runner.calls().len(), 1);
+    }
+}
@@ -1,182 +1,365 @@
 //! macOS execution layer for Luna.
 //!
-//! Executes actions using shell commands via std::process::Command.
+//! Executes actions through `open` and `osascript` via a `CommandRunner`.
+//! Screen brightness uses the `brightness` tool (`brew install brightness`),
+//! as macOS ships no command for it.
 
+use super::clipboard::{run_copy, run_read_clipboard, run_transform_clipboard};
+use super::media::run_now_playing;
+use super::{run_plan, run_level_adjust, CommandPlan, CommandRunner, ExecError, ExecResult, Executor, SystemRunner};
+use crate::config::SearchEngines;
 use crate::intent::Action;
-use std::process::Command;
-use thiserror::Error;
 
-/// Errors that can occur during command execution.
-#[derive(Error, Debug)]
-pub enum ExecError {
-    #[error("Failed to execute command: {0}")]
-    CommandFailed(String),
-
-    #[error("Command returned non-zero exit code: {0}")]
-    NonZeroExit(i32),
-
-    #[error("Failed to spawn process: {0}")]
-    SpawnFailed(#[from] std::io::Error),
+/// Executor for macOS.
+#[derive(Debug, Default)]
+pub struct MacosExecutor<R: CommandRunner = SystemRunner> {
+    runner: R,
+    search: SearchEngines,
 }
 
-/// Result of executing an action.
-#[derive(Debug, Clone)]
-pub struct ExecResult {
-    /// The command that was executed
-    pub command: String,
-    /// Whether execution was successful
-    pub success: bool,
-    /// Optional output from the command
-    pub output: Option<String>,
+impl MacosExecutor {
+    /// Create an executor that runs real processes, using the configured search engines.
+    pub fn new() -> Self {
+        Self::default().with_search_engines(SearchEngines::load_default().unwrap_or_default())
+    }
 }
 
-/// Execute an action on macOS.
-///
-/// # Arguments
-/// * `action` - The parsed action to execute
-///
-/// # Returns
-/// * `Ok(ExecResult)` - Execution completed (check success field)
-/// * `Err(ExecError)` - Failed to execute command
-pub fn execute(action: &Action) -> Result<ExecResult, ExecError> {
-    match action {
-        Action::OpenApp { name } => execute_open_app(name),
-        Action::OpenUrl { url } => execute_open_url(url),
-        Action::SetVolume { level } => execute_set_volume(*level),
-        Action::Mute => execute_mute(),
-        Action::Unmute => execute_unmute(),
+impl<R: CommandRunner> MacosExecutor<R> {
+    /// Create an executor that runs commands through `runner`.
+    pub fn with_runner(runner: R) -> Self {
+        Self {
+            runner,
+            search: SearchEngines::default(),
+        }
+    }
+
+    /// Build web search URLs from `search` instead of the built-in engines.
+    pub fn with_search_engines(mut self, search: SearchEngines) -> Self {
+        self.search = search;
+        self
     }
 }
 
-/// Get the command string that would be executed for an action (for dry-run).
-pub fn get_command_string(action: &Action) -> String {
-    match action {
-        Action::OpenApp { name } => format!("open -a \"{}\"", name),
-        Action::OpenUrl { url } => format!("open \"{}\"", url),
-        Action::SetVolume { level } => {
-            format!("osascript -e 'set volume output volume {}'", level)
+impl<R: CommandRunner> Executor for MacosExecutor<R> {
+    fn plan(&self, action: &Action) -> CommandPlan {
+        match action {
+            // open -a "AppName"
+            Action::OpenApp { name } => CommandPlan::new("open").arg("-a").arg(name),
+            Action::QuitApp { name } => osascript(&format!("tell application {} to quit", applescript_string(name))),
+            // pkill -KILL -x "AppName"
+            Action::ForceQuitApp { name } => CommandPlan::new("pkill").args(["-KILL", "-x"]).arg(name),
+            Action::HideApp { name } => osascript(&format!(
+                "tell application \"System Events\" to set visible of process {} to false",
+                applescript_string(name)
+            )),
+            Action::FocusApp { name } => osascript(&format!("tell application {} to activate", applescript_string(name))),
+            // open "URL"
+            Action::OpenUrl { url } => CommandPlan::new("open").arg(url),
+            // open -R "path" selects it in Finder
+            Action::OpenPath { path, reveal: false } => CommandPlan::new("open").arg(path),
+            Action::OpenPath { path, reveal: true } => CommandPlan::new("open").arg("-R").arg(path),
+            Action::WebSearch { query, engine } => self.plan(&Action::OpenUrl {
+                url: self.search.url(engine, query),
+            }),
+            Action::SetVolume { level } => set_volume(*level),
+            Action::AdjustVolume { .. } => osascript("output volume of (get volume settings)"),
+            Action::Mute => osascript("set volume with output muted"),
+            Action::Unmute => osascript("set volume without output muted"),
+            Action::SetBrightness { level } => set_brightness(*level),
+            // brightness -l lists each display's current level
+            Action::AdjustBrightness { .. } => CommandPlan::new("brightness").arg("-l"),
+            // Control-Command-Q
+            Action::LockScreen => osascript(
+                "tell application \"System Events\" to keystroke \"q\" using {control down, command down}",
+            ),
+            Action::Sleep => CommandPlan::new("pmset").arg("sleepnow"),
+            // Through System Events so apps are asked to quit first
+            Action::Restart => osascript("tell application \"System Events\" to restart"),
+            Action::Shutdown => osascript("tell application \"System Events\" to shut down"),
+            Action::LogOut => osascript("tell application \"System Events\" to log out"),
+            Action::CopyText { text } => pbcopy(text),
+            Action::ReadClipboard | Action::TransformClipboard { .. } => pbpaste(),
+            Action::Notify { title, body } => osascript(&format!(
+                "display notification {} with title {}",
+                applescript_string(body),
+                applescript_string(title)
+            )),
+            Action::Play => media_player("play"),
+            Action::Pause => media_player("pause"),
+            Action::TogglePlayback => media_player("playpause"),
+            Action::NextTrack => media_player("next track"),
+            Action::PreviousTrack => media_player("previous track"),
+            Action::NowPlaying => media_player(NOW_PLAYING_COMMAND),
         }
-        Action::Mute => "osascript -e 'set volume with output muted'".to_string(),
-        Action::Unmute => "osascript -e 'set volume without output muted'".to_string(),
     }
+
+    fn execute(&self, action: &Action) -> Result<ExecResult, ExecError> {
+        match action {
+            Action::AdjustVolume { delta } => run_level_adjust(
+                &self.runner,
+                self.plan(action),
+                parse_volume_level,
+                *delta,
+                set_volume,
+            ),
+            Action::AdjustBrightness { delta } => run_level_adjust(
+                &self.runner,
+                self.plan(action),
+                parse_brightness_level,
+                *delta,
+                set_brightness,
+            ),
+            Action::NowPlaying => run_now_playing(&self.runner, self.plan(action)),
+            Action::CopyText { text } => run_copy(&self.runner, self.plan(action), text),
+            Action::ReadClipboard => run_read_clipboard(&self.runner, self.plan(action)),
+            Action::TransformClipboard { transform } => {
+                run_transform_clipboard(&self.runner, self.plan(action), *transform, pbcopy)
+            }
+            _ => run_plan(&self.runner, self.plan(action)),
+        }
+    }
+}
+
+/// Plan: osascript -e 'set volume output volume <level>'
+fn set_volume(level: u8) -> CommandPlan {
+    osascript(&format!("set volume output volume {}", level))
 }
 
-/// Execute: open -a "AppName"
-fn execute_open_app(name: &str) -> Result<ExecResult, ExecError> {
-    let command_str = format!("open -a \"{}\"", name);
-
-    let output = Command::new("open")
-        .arg("-a")
-        .arg(name)
-        .output()?;
-
-    Ok(ExecResult {
-        command: command_str,
-        success: output.status.success(),
-        output: if output.status.success() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(&output.stderr).to_string())
-        },
-    })
+/// Parse the output of `output volume of (get volume settings)` ("40\n").
+fn parse_volume_level(output: &str) -> Option<u8> {
+    output.trim().parse().ok()
 }
 
-/// Execute: open "URL"
-fn execute_open_url(url: &str) -> Result<ExecResult, ExecError> {
-    let command_str = format!("open \"{}\"", url);
-
-    let output = Command::new("open")
-        .arg(url)
-        .output()?;
-
-    Ok(ExecResult {
-        command: command_str,
-        success: output.status.success(),
-        output: if output.status.success() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(&output.stderr).to_string())
-        },
-    })
+/// Plan: brightness <fraction>, for every display
+fn set_brightness(level: u8) -> CommandPlan {
+    CommandPlan::new("brightness").arg(format!("{:.2}", f32::from(level) / 100.0))
 }
 
-/// Execute: osascript -e 'set volume output volume <level>'
-fn execute_set_volume(level: u8) -> Result<ExecResult, ExecError> {
-    let script = format!("set volume output volume {}", level);
-    let command_str = format!("osascript -e '{}'", script);
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()?;
-
-    Ok(ExecResult {
-        command: command_str,
-        success: output.status.success(),
-        output: if output.status.success() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(&output.stderr).to_string())
-        },
-    })
+/// Parse the first display's level from `brightness -l`
+/// ("display 0: brightness 0.500000").
+fn parse_brightness_level(output: &str) -> Option<u8> {
+    let value: f32 = output
+        .lines()
+        .find_map(|line| line.split_once(": brightness "))?
+        .1
+        .trim()
+        .parse()
+        .ok()?;
+    Some((value * 100.0).round().clamp(0.0, 100.0) as u8)
 }
 
-/// Execute: osascript -e 'set volume with output muted'
-fn execute_mute() -> Result<ExecResult, ExecError> {
-    let script = "set volume with output muted";
-    let command_str = format!("osascript -e '{}'", script);
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()?;
-
-    Ok(ExecResult {
-        command: command_str,
-        success: output.status.success(),
-        output: if output.status.success() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(&output.stderr).to_string())
-        },
-    })
+/// Reports "player\ttitle\tartist\talbum", or nothing if no track is loaded.
+const NOW_PLAYING_COMMAND: &str = "if player state is not stopped then return playerName & tab & \
+name of current track & tab & artist of current track & tab & album of current track";
+
+/// Plan: send `command` to Spotify if it is running, otherwise to Music.
+///
+/// Both apps share Music's playback terms, so one script drives either.
+fn media_player(command: &str) -> CommandPlan {
+    osascript(&format!(
+        "set playerName to \"Music\"\n\
+         if application \"Spotify\" is running then set playerName to \"Spotify\"\n\
+         using terms from application \"Music\"\n\
+         tell application playerName\n{}\nend tell\n\
+         end using terms from",
+        command
+    ))
 }
 
-/// Execute: osascript -e 'set volume without output muted'
-fn execute_unmute() -> Result<ExecResult, ExecError> {
-    let script = "set volume without output muted";
-    let command_str = format!("osascript -e '{}'", script);
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()?;
-
-    Ok(ExecResult {
-        command: command_str,
-        success: output.status.success(),
-        output: if output.status.success() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(&output.stderr).to_string())
-        },
-    })
+/// Plan: pbcopy, with `text` on stdin.
+///
+/// pbcopy and pbpaste assume a non-UTF-8 encoding unless the locale says
+/// otherwise, which mangles non-ASCII text when Luna isn't run from a
+/// terminal.
+fn pbcopy(text: &str) -> CommandPlan {
+    CommandPlan::new("pbcopy").env("LANG", "en_US.UTF-8").stdin(text)
+}
+
+/// Plan: pbpaste
+fn pbpaste() -> CommandPlan {
+    CommandPlan::new("pbpaste").env("LANG", "en_US.UTF-8")
+}
+
+/// Quote text as an AppleScript string literal.
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Plan: osascript -e '<script>'
+fn osascript(script: &str) -> CommandPlan {
+    CommandPlan::new("osascript").arg("-e").arg(script)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exec::{CommandOutput, RecordingRunner};
+    use std::io;
+
+    fn get_command_string(action: &Action) -> String {
+        MacosExecutor::with_runner(RecordingRunner::new())
+            .plan(action)
+            .to_string()
+    }
 
     #[test]
     fn test_get_command_string_open_app() {
         let action = Action::OpenApp { name: "Safari".to_string() };
-        assert_eq!(get_command_string(&action), "open -a \"Safari\"");
+        assert_eq!(get_command_string(&action), "open -a Safari");
     }
 
     #[test]
     fn test_get_command_string_open_url() {
         let action = Action::OpenUrl { url: "https://google.com".to_string() };
-        assert_eq!(get_command_string(&action), "open \"https://google.com\"");
+        assert_eq!(get_command_string(&action), "open https://google.com");
+    }
+
+    #[test]
+    fn test_get_command_string_open_path() {
+        let open = Action::OpenPath { path: "/Users/me/My Notes".to_string(), reveal: false };
+        assert_eq!(get_command_string(&open), "open '/Users/me/My Notes'");
+
+        let reveal = Action::OpenPath { path: "/Users/me/Downloads".to_string(), reveal: true };
+        assert_eq!(get_command_string(&reveal), "open -R /Users/me/Downloads");
+    }
+
+    #[test]
+    fn test_get_command_string_app_control() {
+        let name = || "Slack".to_string();
+        assert_eq!(
+            get_command_string(&Action::QuitApp { name: name() }),
+            r#"osascript -e 'tell application "Slack" to quit'"#
+        );
+        assert_eq!(
+            get_command_string(&Action::ForceQuitApp { name: name() }),
+            "pkill -KILL -x Slack"
+        );
+        assert_eq!(
+            get_command_string(&Action::HideApp { name: name() }),
+            r#"osascript -e 'tell application "System Events" to set visible of process "Slack" to false'"#
+        );
+        assert_eq!(
+            get_command_string(&Action::FocusApp { name: name() }),
+            r#"osascript -e 'tell application "Slack" to activate'"#
+        );
+    }
+
+    #[test]
+    fn test_get_command_string_clipboard() {
+        assert_eq!(
+            get_command_string(&Action::CopyText { text: "me@example.com".to_string() }),
+            "printf '%s' me@example.com | LANG=en_US.UTF-8 pbcopy"
+        );
+        assert_eq!(get_command_string(&Action::ReadClipboard), "LANG=en_US.UTF-8 pbpaste");
+    }
+
+    #[test]
+    fn test_execute_read_clipboard() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("café"));
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor.execute(&Action::ReadClipboard).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.clipboard.as_deref(), Some("café"));
+    }
+
+    #[test]
+    fn test_get_command_string_power() {
+        assert_eq!(get_command_string(&Action::Sleep), "pmset sleepnow");
+        assert_eq!(
+            get_command_string(&Action::Shutdown),
+            r#"osascript -e 'tell application "System Events" to shut down'"#
+        );
+        assert_eq!(
+            get_command_string(&Action::LogOut),
+            r#"osascript -e 'tell application "System Events" to log out'"#
+        );
+    }
+
+    #[test]
+    fn test_get_command_string_set_brightness() {
+        assert_eq!(get_command_string(&Action::SetBrightness { level: 70 }), "brightness 0.70");
+        assert_eq!(get_command_string(&Action::SetBrightness { level: 100 }), "brightness 1.00");
+    }
+
+    #[test]
+    fn test_execute_adjust_brightness_reads_current_level() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success(
+            "display 0: main, active, awake, online, built-in, ID 0x4280a80\ndisplay 0: brightness 0.500000\n",
+        ));
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor.execute(&Action::AdjustBrightness { delta: -20 }).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.command.to_string(), "brightness 0.30");
+        assert_eq!(runner.calls()[0].to_string(), "brightness -l");
+    }
+
+    #[test]
+    fn test_plan_media_targets_running_player() {
+        let executor = MacosExecutor::with_runner(RecordingRunner::new());
+        let plan = executor.plan(&Action::NextTrack);
+        assert_eq!(plan.program, "osascript");
+        assert!(plan.args[1].contains(r#"if application "Spotify" is running"#));
+        assert!(plan.args[1].contains("tell application playerName\nnext track\nend tell"));
+    }
+
+    #[test]
+    fn test_execute_now_playing() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("Spotify\tSo What\tMiles Davis\tKind of Blue\n"));
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor.execute(&Action::NowPlaying).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.output.as_deref(), Some("So What by Miles Davis (Spotify)"));
+        assert_eq!(result.now_playing.unwrap().player, "Spotify");
+    }
+
+    #[test]
+    fn test_get_command_string_notify() {
+        let action = Action::Notify { title: "Luna".to_string(), body: "stand up".to_string() };
+        assert_eq!(
+            get_command_string(&action),
+            r#"osascript -e 'display notification "stand up" with title "Luna"'"#
+        );
+    }
+
+    #[test]
+    fn test_notify_text_cannot_escape_string() {
+        let body = r#"hi" & (do shell script "touch /tmp/pwned") & "\"#;
+        let plan = MacosExecutor::with_runner(RecordingRunner::new())
+            .plan(&Action::Notify { title: "Luna".to_string(), body: body.to_string() });
+        assert_eq!(
+            plan.args[1],
+            r#"display notification "hi\" & (do shell script \"touch /tmp/pwned\") & \"\\" with title "Luna""#
+        );
+    }
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(applescript_string("Safari"), r#""Safari""#);
+        assert_eq!(applescript_string(r#"My "Best" \ App"#), r#""My \"Best\" \\ App""#);
+    }
+
+    #[test]
+    fn test_get_command_string_web_search() {
+        let action = Action::WebSearch { query: "c++ & go".to_string(), engine: "github".to_string() };
+        assert_eq!(
+            get_command_string(&action),
+            "open 'https://github.com/search?q=c%2B%2B%20%26%20go'"
+        );
+    }
+
+    #[test]
+    fn test_get_command_string_quotes_names() {
+        let action = Action::OpenApp { name: "Bob's \"Big\" App".to_string() };
+        assert_eq!(
+            get_command_string(&action),
+            r#"open -a 'Bob'\''s "Big" App'"#
+        );
     }
 
     #[test]
@@ -203,4 +386,102 @@ mod tests {
             "osascript -e 'set volume without output muted'"
         );
     }
+
+    #[test]
+    fn test_execute_open_app() {
+        let runner = RecordingRunner::new();
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor
+            .execute(&Action::OpenApp { name: "Safari".to_string() })
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.command.to_string(), "open -a Safari");
+        assert_eq!(result.output, None);
+        assert_eq!(runner.calls(), vec![CommandPlan::new("open").args(["-a", "Safari"])]);
+    }
+
+    #[test]
+    fn test_execute_open_url() {
+        let runner = RecordingRunner::new();
+        let executor = MacosExecutor::with_runner(&runner);
+        executor
+            .execute(&Action::OpenUrl { url: "https://google.com".to_string() })
+            .unwrap();
+
+        assert_eq!(runner.calls(), vec![CommandPlan::new("open").arg("https://google.com")]);
+    }
+
+    #[test]
+    fn test_execute_volume_and_mute() {
+        let runner = RecordingRunner::new();
+        let executor = MacosExecutor::with_runner(&runner);
+        executor.execute(&Action::SetVolume { level: 40 }).unwrap();
+        executor.execute(&Action::Mute).unwrap();
+        executor.execute(&Action::Unmute).unwrap();
+
+        let scripts: Vec<_> = runner.calls().into_iter().map(|c| c.args).collect();
+        assert_eq!(
+            scripts,
+            vec![
+                vec!["-e".to_string(), "set volume output volume 40".to_string()],
+                vec!["-e".to_string(), "set volume with output muted".to_string()],
+                vec!["-e".to_string(), "set volume without output muted".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_adjust_volume_reads_current_level() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("95\n"));
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor.execute(&Action::AdjustVolume { delta: 10 }).unwrap();
+
+        assert!(result.success);
+        assert_eq!(
+            result.command.to_string(),
+            "osascript -e 'set volume output volume 100'"
+        );
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].args[1], "output volume of (get volume settings)");
+    }
+
+    #[test]
+    fn test_execute_adjust_volume_unreadable_level() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("missing value"));
+        let executor = MacosExecutor::with_runner(&runner);
+
+        let err = executor.execute(&Action::AdjustVolume { delta: -10 }).unwrap_err();
+        assert!(matches!(err, ExecError::CommandFailed(_)));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_execute_failure_fills_output() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::failure(1, "Unable to find application named 'Nope'"));
+        let executor = MacosExecutor::with_runner(&runner);
+        let result = executor
+            .execute(&Action::OpenApp { name: "Nope".to_string() })
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(
+            result.output.as_deref(),
+            Some("Unable to find application named 'Nope'")
+        );
+    }
+
+    #[test]
+    fn test_execute_spawn_error() {
+        let runner = RecordingRunner::new();
+        runner.push_error(io::ErrorKind::NotFound, "osascript not found");
+        let executor = MacosExecutor::with_runner(&runner);
+
+        let err = executor.execute(&Action::Mute).unwrap_err();
+        assert!(matches!(err, ExecError::SpawnFailed(_)));
+    }
 }
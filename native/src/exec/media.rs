@@ -0,0 +1,122 @@
+//! Media playback support for Luna.
+//!
+//! Both backends report the current track as one tab-separated line
+//! (player, title, artist, album), which is parsed here into `NowPlaying`.
+
+use super::{CommandPlan, CommandRunner, ExecError, ExecResult};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// The track a media player is playing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct NowPlaying {
+    /// Player reporting the track ("Spotify", "Music", "vlc")
+    pub player: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+}
+
+impl fmt::Display for NowPlaying {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.title)?;
+        if let Some(ref artist) = self.artist {
+            write!(f, " by {}", artist)?;
+        }
+        write!(f, " ({})", self.player)
+    }
+}
+
+/// Parse a "player\ttitle\tartist\talbum" line; `None` if no title is given.
+pub fn parse_now_playing(output: &str) -> Option<NowPlaying> {
+    let line = output.lines().find(|line| !line.trim().is_empty())?;
+    let mut fields = line.split('\t').map(str::trim);
+    let present = |field: Option<&str>| field.filter(|f| !f.is_empty()).map(str::to_string);
+
+    let player = present(fields.next())?;
+    let title = present(fields.next())?;
+    Some(NowPlaying {
+        player,
+        title,
+        artist: present(fields.next()),
+        album: present(fields.next()),
+    })
+}
+
+/// Run a "now playing" query and attach the parsed track to the result.
+///
+/// A query that succeeds without a track (nothing playing) is still a
+/// success, with `now_playing` left empty.
+pub(crate) fn run_now_playing<R: CommandRunner + ?Sized>(
+    runner: &R,
+    plan: CommandPlan,
+) -> Result<ExecResult, ExecError> {
+    let output = runner.run(&plan)?;
+    if !output.success {
+        return Ok(ExecResult {
+            command: plan,
+            success: false,
+            output: Some(output.stderr),
+            now_playing: None,
+            clipboard: None,
+        });
+    }
+
+    let now_playing = parse_now_playing(&output.stdout);
+    Ok(ExecResult {
+        command: plan,
+        success: true,
+        output: Some(match now_playing {
+            Some(ref track) => track.to_string(),
+            None => "Nothing is playing".to_string(),
+        }),
+        now_playing,
+        clipboard: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{CommandOutput, RecordingRunner};
+
+    #[test]
+    fn test_parse_now_playing() {
+        assert_eq!(
+            parse_now_playing("Spotify\tSo What\tMiles Davis\tKind of Blue\n"),
+            Some(NowPlaying {
+                player: "Spotify".to_string(),
+                title: "So What".to_string(),
+                artist: Some("Miles Davis".to_string()),
+                album: Some("Kind of Blue".to_string()),
+            })
+        );
+
+        let track = parse_now_playing("vlc\tstream.mp3\t\t").unwrap();
+        assert_eq!(track.artist, None);
+        assert_eq!(track.to_string(), "stream.mp3 (vlc)");
+
+        assert_eq!(parse_now_playing(""), None);
+        assert_eq!(parse_now_playing("Music\t\t\t"), None);
+    }
+
+    #[test]
+    fn test_run_now_playing() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::success("Music\tSo What\tMiles Davis\tKind of Blue\n"));
+        runner.push_output(CommandOutput::success(""));
+
+        let result = run_now_playing(&runner, CommandPlan::new("query")).unwrap();
+        assert!(result.success);
+        assert_eq!(result.output.as_deref(), Some("So What by Miles Davis (Music)"));
+        assert_eq!(result.now_playing.unwrap().album.as_deref(), Some("Kind of Blue"));
+
+        let result = run_now_playing(&runner, CommandPlan::new("query")).unwrap();
+        assert!(result.success);
+        assert_eq!(result.now_playing, None);
+        assert_eq!(result.output.as_deref(), Some("Nothing is playing"));
+    }
+}
@@ -1,8 +1,172 @@
 //! Execution module for Luna.
 //!
-//! Platform-specific command execution logic.
+//! Platform-specific command execution logic. Each backend implements the
+//! `Executor` trait by turning an action into a `CommandPlan` and running it
+//! through a `CommandRunner`, so both are always compiled and can be tested
+//! anywhere; the one matching the target platform is used by `execute`/`plan`.
 
+pub mod clipboard;
+pub mod linux;
 pub mod macos;
+pub mod media;
+pub mod plan;
+pub mod runner;
 
-// Re-export for convenience
-pub use macos::{execute, get_command_string, ExecError, ExecResult};
+pub use linux::LinuxExecutor;
+pub use macos::MacosExecutor;
+pub use media::NowPlaying;
+pub use plan::CommandPlan;
+pub use runner::{CommandOutput, CommandRunner, RecordingRunner, SystemRunner};
+
+use crate::intent::Action;
+use std::env;
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors that can occur during command execution.
+#[derive(Error, Debug)]
+pub enum ExecError {
+    #[error("Failed to execute command: {0}")]
+    CommandFailed(String),
+
+    #[error("Command returned non-zero exit code: {0}")]
+    NonZeroExit(i32),
+
+    #[error("Failed to spawn process: {0}")]
+    SpawnFailed(#[from] std::io::Error),
+}
+
+/// Result of executing an action.
+#[derive(Debug, Clone)]
+pub struct ExecResult {
+    /// The command that was executed
+    pub command: CommandPlan,
+    /// Whether execution was successful
+    pub success: bool,
+    /// Optional output from the command
+    pub output: Option<String>,
+    /// The current track, for media actions that report it
+    pub now_playing: Option<NowPlaying>,
+    /// The clipboard text after a clipboard action
+    pub clipboard: Option<String>,
+}
+
+/// A platform backend that turns actions into system commands.
+///
+/// Executors are shared between connections by `luna daemon`, so they must be
+/// `Send + Sync`.
+pub trait Executor: Send + Sync {
+    /// Build the command that implements an action (also used for dry-run).
+    ///
+    /// For `AdjustVolume` this is the query that reads the current level;
+    /// the command that sets the new level depends on its output.
+    fn plan(&self, action: &Action) -> CommandPlan;
+
+    /// Execute an action.
+    ///
+    /// # Returns
+    /// * `Ok(ExecResult)` - Execution completed (check success field)
+    /// * `Err(ExecError)` - Failed to execute command
+    fn execute(&self, action: &Action) -> Result<ExecResult, ExecError>;
+}
+
+/// Create the executor for the current platform, backed by real processes.
+pub fn default_executor() -> Box<dyn Executor> {
+    if cfg!(target_os = "linux") {
+        Box::new(LinuxExecutor::new())
+    } else {
+        Box::new(MacosExecutor::new())
+    }
+}
+
+/// Execute an action with the current platform's executor.
+pub fn execute(action: &Action) -> Result<ExecResult, ExecError> {
+    default_executor().execute(action)
+}
+
+/// Get the command the current platform's executor would run (for dry-run).
+pub fn plan(action: &Action) -> CommandPlan {
+    default_executor().plan(action)
+}
+
+/// Check whether an executable with the given name exists on `PATH`.
+pub(crate) fn is_on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file()))
+        .unwrap_or(false)
+}
+
+/// Apply a relative change to a volume or brightness level, clamped to 0-100.
+pub fn adjust_level(current: u8, delta: i8) -> u8 {
+    (current as i16 + delta as i16).clamp(0, 100) as u8
+}
+
+/// Read the current volume or brightness with `query`, then run `set` for
+/// the adjusted level.
+///
+/// If the query itself fails its result is returned as-is; if its output
+/// cannot be understood, `ExecError::CommandFailed` is returned.
+pub(crate) fn run_level_adjust<R: CommandRunner + ?Sized>(
+    runner: &R,
+    query: CommandPlan,
+    parse_level: impl Fn(&str) -> Option<u8>,
+    delta: i8,
+    set: impl FnOnce(u8) -> CommandPlan,
+) -> Result<ExecResult, ExecError> {
+    let output = runner.run(&query)?;
+    if !output.success {
+        return Ok(ExecResult {
+            command: query,
+            success: false,
+            output: Some(output.stderr),
+            now_playing: None,
+            clipboard: None,
+        });
+    }
+
+    let current = parse_level(&output.stdout).ok_or_else(|| {
+        ExecError::CommandFailed(format!(
+            "could not read current level from `{}`: {}",
+            query,
+            output.stdout.trim()
+        ))
+    })?;
+
+    run_plan(runner, set(adjust_level(current, delta)))
+}
+
+/// Run a plan through `runner` and convert its output to an `ExecResult`.
+///
+/// On failure the command's stderr is stored in `ExecResult.output`.
+pub(crate) fn run_plan<R: CommandRunner + ?Sized>(
+    runner: &R,
+    plan: CommandPlan,
+) -> Result<ExecResult, ExecError> {
+    let output = runner.run(&plan)?;
+
+    Ok(ExecResult {
+        command: plan,
+        success: output.success,
+        output: if output.success {
+            None
+        } else {
+            Some(output.stderr)
+        },
+        now_playing: None,
+        clipboard: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_level_clamps() {
+        assert_eq!(adjust_level(40, 10), 50);
+        assert_eq!(adjust_level(95, 10), 100);
+        assert_eq!(adjust_level(5, -10), 0);
+        assert_eq!(adjust_level(0, -100), 0);
+        assert_eq!(adjust_level(100, 100), 100);
+    }
+}
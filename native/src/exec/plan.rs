@@ -0,0 +1,177 @@
+//! Command plans for Luna.
+//!
+//! A `CommandPlan` is the single description of a process invocation: the
+//! executor runs it, dry-run prints it, and `--json` exposes its argv. The
+//! human-readable form is POSIX-shell-safe, so it can be pasted into a
+//! terminal and runs exactly what Luna would run.
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt;
+
+/// A single process invocation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommandPlan {
+    /// Program to run
+    pub program: String,
+    /// Arguments passed to the program
+    pub args: Vec<String>,
+    /// Extra environment variables
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<(String, String)>,
+    /// Optional script written to the process's stdin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdin: Option<String>,
+    /// Send the process's stdout and stderr to /dev/null instead of
+    /// capturing them
+    #[serde(skip_serializing_if = "is_false")]
+    pub discard_output: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl CommandPlan {
+    /// Create a plan for `program` with no arguments.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Append an argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Append several arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable for the process.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Feed `input` to the process on stdin.
+    pub fn stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    /// Don't capture the process's output.
+    ///
+    /// Needed for tools that leave a background process holding stdout
+    /// open (`xclip`, `wl-copy`), which would otherwise never be read to
+    /// the end.
+    pub fn discard_output(mut self) -> Self {
+        self.discard_output = true;
+        self
+    }
+
+    /// The program followed by its arguments.
+    pub fn argv(&self) -> Vec<String> {
+        std::iter::once(self.program.clone())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+
+    /// Render the plan as a POSIX shell command line.
+    pub fn to_shell(&self) -> String {
+        let mut parts: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shell_quote(value)))
+            .collect();
+        parts.extend(self.argv().iter().map(|arg| shell_quote(arg).into_owned()));
+        let mut command = parts.join(" ");
+        if self.discard_output {
+            command.push_str(" >/dev/null 2>&1");
+        }
+
+        match &self.stdin {
+            Some(input) => format!("printf '%s' {} | {}", shell_quote(input), command),
+            None => command,
+        }
+    }
+}
+
+impl fmt::Display for CommandPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_shell())
+    }
+}
+
+/// Quote a word for a POSIX shell.
+///
+/// Words made only of characters with no special meaning are returned as-is;
+/// anything else is wrapped in single quotes, with embedded single quotes
+/// written as `'\''`.
+pub fn shell_quote(word: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(format!("'{}'", word.replace('\'', r"'\''")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("Safari"), "Safari");
+        assert_eq!(shell_quote("https://google.com/a?b=c"), "'https://google.com/a?b=c'");
+        assert_eq!(shell_quote("Visual Studio Code"), "'Visual Studio Code'");
+        assert_eq!(shell_quote("Bob's App"), r"'Bob'\''s App'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+    }
+
+    #[test]
+    fn test_to_shell() {
+        let plan = CommandPlan::new("osascript").args(["-e", "set volume output volume 40"]);
+        assert_eq!(plan.to_shell(), "osascript -e 'set volume output volume 40'");
+        assert_eq!(plan.to_string(), plan.to_shell());
+    }
+
+    #[test]
+    fn test_to_shell_with_env_and_stdin() {
+        let plan = CommandPlan::new("pbcopy").env("LANG", "en_US.UTF-8").stdin("it's");
+        assert_eq!(
+            plan.to_shell(),
+            r"printf '%s' 'it'\''s' | LANG=en_US.UTF-8 pbcopy"
+        );
+    }
+
+    #[test]
+    fn test_to_shell_discarding_output() {
+        let plan = CommandPlan::new("xclip").args(["-selection", "clipboard"]).stdin("hi").discard_output();
+        assert_eq!(plan.to_shell(), "printf '%s' hi | xclip -selection clipboard >/dev/null 2>&1");
+    }
+
+    #[test]
+    fn test_argv() {
+        let plan = CommandPlan::new("open").args(["-a", "Safari"]);
+        assert_eq!(plan.argv(), vec!["open", "-a", "Safari"]);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let plan = CommandPlan::new("open").arg("-a").arg("Safari");
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(json, r#"{"program":"open","args":["-a","Safari"]}"#);
+    }
+}
@@ -0,0 +1,194 @@
+//! Command runners for Luna.
+//!
+//! Executors never call `std::process::Command` directly; they hand a
+//! `CommandPlan` to a `CommandRunner`. `SystemRunner` spawns real processes,
+//! while `RecordingRunner` records every call and replays scripted outputs
+//! so execution paths can be tested on any machine.
+
+use super::CommandPlan;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+/// Captured output of a finished process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Whether the process exited successfully
+    pub success: bool,
+    /// Exit code, if the process exited normally
+    pub code: Option<i32>,
+    /// Captured stdout
+    pub stdout: String,
+    /// Captured stderr
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// A successful run that printed `stdout`.
+    pub fn success(stdout: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            code: Some(0),
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+
+    /// A failed run with the given exit code and stderr.
+    pub fn failure(code: i32, stderr: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            code: Some(code),
+            stdout: String::new(),
+            stderr: stderr.into(),
+        }
+    }
+}
+
+/// Something that can run a `CommandPlan`.
+pub trait CommandRunner: Send + Sync {
+    /// Run the command to completion and capture its output.
+    fn run(&self, plan: &CommandPlan) -> io::Result<CommandOutput>;
+}
+
+/// Runs commands as real child processes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, plan: &CommandPlan) -> io::Result<CommandOutput> {
+        let mut command = Command::new(&plan.program);
+        command
+            .args(&plan.args)
+            .envs(plan.env.iter().map(|(k, v)| (k, v)))
+            .stdout(if plan.discard_output { Stdio::null() } else { Stdio::piped() })
+            .stderr(if plan.discard_output { Stdio::null() } else { Stdio::piped() })
+            .stdin(if plan.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            });
+
+        let mut child = command.spawn()?;
+        if let (Some(input), Some(mut stdin)) = (&plan.stdin, child.stdin.take()) {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        Ok(CommandOutput {
+            success: output.status.success(),
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+/// Records every command and replays scripted outputs in order.
+///
+/// When the script runs out, each further call succeeds with empty output.
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    calls: Mutex<Vec<CommandPlan>>,
+    outputs: Mutex<VecDeque<io::Result<CommandOutput>>>,
+}
+
+impl RecordingRunner {
+    /// Create a runner with an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue the output returned by the next unscripted call.
+    pub fn push_output(&self, output: CommandOutput) -> &Self {
+        self.outputs.lock().unwrap().push_back(Ok(output));
+        self
+    }
+
+    /// Queue a spawn error for the next unscripted call.
+    pub fn push_error(&self, kind: io::ErrorKind, message: &str) -> &Self {
+        self.outputs
+            .lock()
+            .unwrap()
+            .push_back(Err(io::Error::new(kind, message.to_string())));
+        self
+    }
+
+    /// All commands run so far, in order.
+    pub fn calls(&self) -> Vec<CommandPlan> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, plan: &CommandPlan) -> io::Result<CommandOutput> {
+        self.calls.lock().unwrap().push(plan.clone());
+        self.outputs
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Ok(CommandOutput::success("")))
+    }
+}
+
+impl<R: CommandRunner + ?Sized> CommandRunner for &R {
+    fn run(&self, plan: &CommandPlan) -> io::Result<CommandOutput> {
+        (**self).run(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_runner_records_calls() {
+        let runner = RecordingRunner::new();
+        let plan = CommandPlan::new("pbcopy").env("LANG", "C").stdin("hello");
+        runner.run(&plan).unwrap();
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].program, "pbcopy");
+        assert_eq!(calls[0].env, vec![("LANG".to_string(), "C".to_string())]);
+        assert_eq!(calls[0].stdin.as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn test_recording_runner_replays_script() {
+        let runner = RecordingRunner::new();
+        runner
+            .push_output(CommandOutput::failure(1, "boom"))
+            .push_error(io::ErrorKind::NotFound, "missing");
+
+        let first = runner.run(&CommandPlan::new("a")).unwrap();
+        assert!(!first.success);
+        assert_eq!(first.stderr, "boom");
+
+        let second = runner.run(&CommandPlan::new("b")).unwrap_err();
+        assert_eq!(second.kind(), io::ErrorKind::NotFound);
+
+        // Script exhausted: default success
+        assert!(runner.run(&CommandPlan::new("c")).unwrap().success);
+    }
+
+    #[test]
+    fn test_system_runner_pipes_stdin() {
+        let output = SystemRunner
+            .run(&CommandPlan::new("cat").stdin("luna"))
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "luna");
+    }
+
+    #[test]
+    fn test_system_runner_discarded_output_does_not_wait_for_background_children() {
+        let plan = CommandPlan::new("sh").args(["-c", "sleep 30 & echo luna"]).discard_output();
+        let started = std::time::Instant::now();
+        let output = SystemRunner.run(&plan).unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "");
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,347 @@
+//! Local HTTP API for Luna (the `http` feature).
+//!
+//! `luna serve` answers:
+//!
+//! * `POST /v1/command` - run a command, or plan it with `"dry_run": true`
+//! * `POST /v1/parse` - interpret a command without running it
+//! * `GET /v1/actions` - every action Luna knows, with an example command
+//! * `GET /v1/openapi.json` - the OpenAPI description of this API
+//!
+//! Request bodies take the same parameters as `luna daemon` and results have
+//! the same shape as `luna --json`; whether a command worked is in the
+//! result's `status`, not the HTTP status code. The server listens on
+//! 127.0.0.1 unless told otherwise. With a token, every request except the
+//! OpenAPI document needs `Authorization: Bearer <token>`, and listening on
+//! any other address requires one.
+
+use crate::daemon::{CommandParams, Handler, Mode, Reply};
+use crate::intent::parse::ACTION_EXAMPLES;
+use crate::intent::{parse_intent, Action};
+use crate::output::{JsonOutput, JsonSequenceOutput};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::thread;
+use thiserror::Error;
+use tiny_http::{Header, Response, Server};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+/// Port `luna serve` listens on by default.
+pub const DEFAULT_PORT: u16 = 7788;
+
+/// Environment variable holding the bearer token, so it needn't appear in
+/// the process list.
+pub const TOKEN_ENV: &str = "LUNA_HTTP_TOKEN";
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY: u64 = 64 * 1024;
+
+/// Errors from starting the HTTP server.
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("Listening on {0} needs a bearer token (--token or ${TOKEN_ENV})")]
+    TokenRequired(IpAddr),
+
+    #[error("Could not listen on {0}: {1}")]
+    Bind(SocketAddr, String),
+}
+
+/// Body of `POST /v1/command`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CommandRequest {
+    #[serde(flatten)]
+    pub params: CommandParams,
+    /// Show the command that would run instead of running it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// An entry of `GET /v1/actions`.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ActionInfo {
+    /// The action's `type` tag
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// A command that produces this action
+    pub example: String,
+    /// The action parsed from `example`
+    pub action: Action,
+    /// Whether `/v1/command` runs it only with `"yes": true`
+    pub needs_confirmation: bool,
+}
+
+/// Body of an error response.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+/// The OpenAPI description of the API.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Luna", description = "Natural language system control for macOS and Linux"),
+    paths(command, parse, actions),
+    components(schemas(Action, JsonOutput, JsonSequenceOutput, ErrorBody)),
+    modifiers(&BearerAuth),
+    security(("bearer" = []))
+)]
+pub struct ApiDoc;
+
+/// Adds the bearer token security scheme to the document.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme("bearer", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
+    }
+}
+
+/// Run or plan a command.
+#[utoipa::path(
+    post,
+    path = "/v1/command",
+    request_body = CommandRequest,
+    responses(
+        (status = 200, description = "The outcome; see `status`", body = Reply),
+        (status = 400, description = "Malformed body", body = ErrorBody),
+        (status = 401, description = "Missing or wrong bearer token", body = ErrorBody),
+    )
+)]
+fn command(handler: &Handler, request: &CommandRequest) -> Reply {
+    let mode = if request.dry_run { Mode::DryRun } else { Mode::Execute };
+    handler.run(mode, &request.params)
+}
+
+/// Interpret a command without running it.
+#[utoipa::path(
+    post,
+    path = "/v1/parse",
+    request_body = CommandParams,
+    responses(
+        (status = 200, description = "The interpretation; status is \"parsed\" or \"error\"", body = Reply),
+        (status = 400, description = "Malformed body", body = ErrorBody),
+        (status = 401, description = "Missing or wrong bearer token", body = ErrorBody),
+    )
+)]
+fn parse(handler: &Handler, params: &CommandParams) -> Reply {
+    handler.run(Mode::Parse, params)
+}
+
+/// List the actions Luna knows.
+#[utoipa::path(
+    get,
+    path = "/v1/actions",
+    responses(
+        (status = 200, description = "One entry per action", body = Vec<ActionInfo>),
+        (status = 401, description = "Missing or wrong bearer token", body = ErrorBody),
+    )
+)]
+fn actions() -> Vec<ActionInfo> {
+    ACTION_EXAMPLES
+        .iter()
+        .filter_map(|example| {
+            let action = parse_intent(example).ok()?;
+            Some(ActionInfo {
+                kind: action.kind().to_string(),
+                example: example.to_string(),
+                needs_confirmation: action.confirmation_prompt().is_some(),
+                action,
+            })
+        })
+        .collect()
+}
+
+/// Routes requests to the daemon's handler, checking the bearer token.
+pub struct Api {
+    handler: Handler,
+    token: Option<String>,
+}
+
+impl Api {
+    pub fn new(handler: Handler, token: Option<String>) -> Self {
+        Api { handler, token }
+    }
+
+    /// Answer a request with an HTTP status code and a JSON body.
+    pub fn respond(&self, method: &str, url: &str, authorization: Option<&str>, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or_default();
+        if (method, path) == ("GET", "/v1/openapi.json") {
+            return ok(ApiDoc::openapi());
+        }
+        if !self.authorized(authorization) {
+            return error(401, "Missing or wrong bearer token");
+        }
+
+        match (method, path) {
+            ("POST", "/v1/command") => match serde_json::from_str::<CommandRequest>(body) {
+                Ok(request) => ok(command(&self.handler, &request)),
+                Err(e) => error(400, &e.to_string()),
+            },
+            ("POST", "/v1/parse") => match serde_json::from_str::<CommandParams>(body) {
+                Ok(params) => ok(parse(&self.handler, &params)),
+                Err(e) => error(400, &e.to_string()),
+            },
+            ("GET", "/v1/actions") => ok(actions()),
+            (_, "/v1/command" | "/v1/parse" | "/v1/actions") => error(405, "Method not allowed"),
+            _ => error(404, "Not found"),
+        }
+    }
+
+    /// Whether `authorization` carries the token (always, without one).
+    fn authorized(&self, authorization: Option<&str>) -> bool {
+        let Some(ref token) = self.token else {
+            return true;
+        };
+        let given = authorization.and_then(|value| value.strip_prefix("Bearer ")).unwrap_or_default();
+        // Compare every byte so the time taken doesn't reveal the token
+        given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
+fn ok(body: impl Serialize) -> (u16, Value) {
+    match serde_json::to_value(body) {
+        Ok(body) => (200, body),
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!(ErrorBody { error: message.to_string() }))
+}
+
+/// Start listening; a token is required for any address but loopback.
+pub fn bind(address: SocketAddr, token: Option<&str>) -> Result<Server, HttpError> {
+    if !address.ip().is_loopback() && token.is_none() {
+        return Err(HttpError::TokenRequired(address.ip()));
+    }
+    Server::http(address).map_err(|e| HttpError::Bind(address, e.to_string()))
+}
+
+/// Answer requests until the server is shut down, each on its own thread.
+pub fn serve(server: Server, api: Arc<Api>) {
+    for mut request in server.incoming_requests() {
+        let api = Arc::clone(&api);
+        thread::spawn(move || {
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.to_string());
+
+            let mut body = String::new();
+            let read = request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body);
+            let (status, json) = match read {
+                Err(e) => error(400, &e.to_string()),
+                Ok(len) if len as u64 > MAX_BODY => error(413, "Request body too large"),
+                Ok(_) => api.respond(request.method().as_str(), request.url(), authorization.as_deref(), &body),
+            };
+
+            let content_type = Header::from_bytes("Content-Type", "application/json").expect("valid header");
+            let response = Response::from_string(json.to_string()).with_status_code(status).with_header(content_type);
+            if let Err(e) = request.respond(response) {
+                eprintln!("Warning: could not send response: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::linux::AudioTool;
+    use crate::exec::{CommandOutput, LinuxExecutor, RecordingRunner};
+    use crate::{Config, Luna};
+
+    fn api(token: Option<&str>) -> (Api, &'static RecordingRunner) {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(RecordingRunner::new()));
+        let executor = LinuxExecutor::with_runner(runner).with_audio_tool(AudioTool::Wpctl);
+        let handler = Handler::new(Luna::with_executor(Config::default(), Box::new(executor)));
+        (Api::new(handler, token.map(str::to_string)), runner)
+    }
+
+    #[test]
+    fn test_command() {
+        let (api, runner) = api(None);
+
+        let (status, body) = api.respond("POST", "/v1/command", None, r#"{"input": "mute", "dry_run": true}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "dry-run");
+        assert!(runner.calls().is_empty());
+
+        runner.push_output(CommandOutput::success(""));
+        let (status, body) = api.respond("POST", "/v1/command", None, r#"{"input": "mute"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "success");
+        assert_eq!(runner.calls().len(), 1);
+
+        let (status, body) = api.respond("POST", "/v1/command", None, r#"{"text": "mute"}"#);
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("input"));
+    }
+
+    #[test]
+    fn test_parse() {
+        let (api, runner) = api(None);
+        let (status, body) = api.respond("POST", "/v1/parse?v=1", None, r#"{"input": "restart"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "parsed");
+        assert_eq!(body["intent"], "Restart");
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_actions() {
+        let actions = actions();
+        assert_eq!(actions.len(), ACTION_EXAMPLES.len());
+        assert_eq!(actions[0].kind, "OpenApp");
+        assert_eq!(actions[0].action, Action::OpenApp { name: "Safari".to_string() });
+
+        let restart = actions.iter().find(|info| info.kind == "Restart").unwrap();
+        assert!(restart.needs_confirmation);
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let (api, _) = api(Some("s3cret"));
+
+        assert_eq!(api.respond("GET", "/v1/actions", None, "").0, 401);
+        assert_eq!(api.respond("GET", "/v1/actions", Some("Bearer wrong!"), "").0, 401);
+        assert_eq!(api.respond("GET", "/v1/actions", Some("s3cret"), "").0, 401);
+        assert_eq!(api.respond("GET", "/v1/actions", Some("Bearer s3cret"), "").0, 200);
+        // The description of the API is public
+        assert_eq!(api.respond("GET", "/v1/openapi.json", None, "").0, 200);
+    }
+
+    #[test]
+    fn test_routing_errors() {
+        let (api, _) = api(None);
+        assert_eq!(api.respond("GET", "/v1/command", None, "").0, 405);
+        assert_eq!(api.respond("GET", "/v2/command", None, "").0, 404);
+    }
+
+    #[test]
+    fn test_openapi_describes_actions() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in ["/v1/command", "/v1/parse", "/v1/actions"] {
+            assert!(doc["paths"][path].is_object(), "{} is documented", path);
+        }
+
+        let schemas = &doc["components"]["schemas"];
+        let action = serde_json::to_string(&schemas["Action"]).unwrap();
+        for name in ["OpenApp", "SetVolume", "TransformClipboard", "Notify"] {
+            assert!(action.contains(name), "Action schema lists {}", name);
+        }
+        assert!(schemas["CommandRequest"].is_object());
+        assert!(doc["components"]["securitySchemes"]["bearer"].is_object());
+    }
+
+    #[test]
+    fn test_bind_requires_token_off_loopback() {
+        let address: SocketAddr = "0.0.0.0:0".parse().unwrap();
+        assert!(matches!(bind(address, None), Err(HttpError::TokenRequired(_))));
+    }
+}
@@ -0,0 +1,439 @@
+//! Fuzzy and phonetic matching for Luna.
+//!
+//! Voice transcripts mangle words in predictable ways: letters get swapped
+//! ("opne"), words get split ("sa fari", "fire fox") or spelled the way they
+//! sound ("crome"). This module matches such words against a known
+//! vocabulary using edit distance and a Metaphone-style phonetic key, and
+//! reports how each correction was made.
+
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// App names matched against when no installed-app catalog is available.
+pub const COMMON_APPS: &[&str] = &[
+    "App Store",
+    "Calculator",
+    "Calendar",
+    "Discord",
+    "Finder",
+    "Firefox",
+    "Google Chrome",
+    "iTerm",
+    "Mail",
+    "Maps",
+    "Messages",
+    "Music",
+    "Notes",
+    "Photos",
+    "Preview",
+    "Reminders",
+    "Safari",
+    "Slack",
+    "Spotify",
+    "System Settings",
+    "Terminal",
+    "Visual Studio Code",
+    "Zoom",
+];
+
+/// Settings for fuzzy matching.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzyConfig {
+    /// Whether fuzzy correction is attempted at all
+    pub enabled: bool,
+    /// Maximum edit distance, as a fraction of the longer word's length
+    pub threshold: f64,
+    /// Whether words that sound alike may match beyond the edit threshold
+    pub phonetic: bool,
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.3,
+            phonetic: true,
+        }
+    }
+}
+
+impl FuzzyConfig {
+    /// A config that never corrects anything.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// How a fuzzy match was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMethod {
+    /// Same letters once spaces and punctuation are removed ("fire fox")
+    Joined,
+    /// Within the edit-distance threshold ("opne")
+    EditDistance,
+    /// Same phonetic key ("crome")
+    Phonetic,
+}
+
+impl fmt::Display for MatchMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchMethod::Joined => write!(f, "joined words"),
+            MatchMethod::EditDistance => write!(f, "edit distance"),
+            MatchMethod::Phonetic => write!(f, "phonetic"),
+        }
+    }
+}
+
+/// What part of the command a correction applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionKind {
+    /// The command verb ("opne" -> "open")
+    Verb,
+    /// An application name ("fire fox" -> "Firefox")
+    AppName,
+}
+
+/// A correction applied while interpreting a command.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Correction {
+    pub kind: CorrectionKind,
+    /// The text as heard
+    pub from: String,
+    /// The text it was corrected to
+    pub to: String,
+    pub method: MatchMethod,
+    /// Similarity between `from` and `to` (0.0-1.0)
+    pub score: f64,
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" -> \"{}\" ({})", self.from, self.to, self.method)
+    }
+}
+
+/// A vocabulary entry that matched a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch<'a> {
+    pub candidate: &'a str,
+    pub method: MatchMethod,
+    /// Similarity between query and candidate (0.0-1.0)
+    pub score: f64,
+}
+
+/// Find the candidate closest to `query`.
+///
+/// Returns `None` if nothing is close enough, or if `query` already equals a
+/// candidate (ignoring case), since there is nothing to correct.
+pub fn best_match<'a, S: AsRef<str>>(
+    query: &str,
+    candidates: &'a [S],
+    config: &FuzzyConfig,
+) -> Option<FuzzyMatch<'a>> {
+    ranked_matches(query, candidates, config).into_iter().next()
+}
+
+/// Find every candidate close enough to `query`, best first.
+///
+/// Returns an empty list if `query` already equals a candidate (ignoring
+/// case), since there is nothing to correct.
+pub fn ranked_matches<'a, S: AsRef<str>>(
+    query: &str,
+    candidates: &'a [S],
+    config: &FuzzyConfig,
+) -> Vec<FuzzyMatch<'a>> {
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let query_key = match_key(query);
+    if query_key.is_empty() {
+        return Vec::new();
+    }
+    let query_sound = metaphone(&query_key);
+
+    let mut matches: Vec<FuzzyMatch<'a>> = Vec::new();
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        if candidate.eq_ignore_ascii_case(query) {
+            return Vec::new();
+        }
+
+        let candidate_key = match_key(candidate);
+        let score = similarity(&query_key, &candidate_key);
+        let method = if query_key == candidate_key {
+            MatchMethod::Joined
+        } else if 1.0 - score <= config.threshold {
+            MatchMethod::EditDistance
+        } else if config.phonetic
+            && !query_sound.is_empty()
+            && query_sound == metaphone(&candidate_key)
+            // Keep phonetic matches from pairing wildly different words
+            && score >= 0.5
+        {
+            MatchMethod::Phonetic
+        } else {
+            continue;
+        };
+
+        matches.push(FuzzyMatch {
+            candidate,
+            method,
+            score,
+        });
+    }
+
+    // Stable sort keeps vocabulary order for equal scores
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches
+}
+
+/// Lowercase ASCII letters and digits only ("Sa Fari" -> "safari").
+fn match_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Similarity from edit distance: 1.0 for identical, 0.0 for nothing in common.
+fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters (optimal string alignment).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+/// Metaphone-style phonetic key for a word.
+///
+/// A simplified version of Lawrence Philips' Metaphone: vowels are dropped
+/// except at the start, and consonant groups that sound alike share a code
+/// ("chrome" and "crome" both become "KRM").
+pub fn metaphone(word: &str) -> String {
+    let mut chars: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    chars.dedup_by(|a, b| a == b && *a != 'C');
+
+    // Silent or simplified initial letters
+    match chars.as_slice() {
+        ['K', 'N', ..] | ['G', 'N', ..] | ['P', 'N', ..] | ['A', 'E', ..] | ['W', 'R', ..] => {
+            chars.remove(0);
+        }
+        ['X', ..] => chars[0] = 'S',
+        ['W', 'H', ..] => {
+            chars.remove(1);
+        }
+        _ => {}
+    }
+
+    let is_vowel = |c: Option<&char>| matches!(c, Some('A' | 'E' | 'I' | 'O' | 'U'));
+    let is_front_vowel = |c: Option<&char>| matches!(c, Some('E' | 'I' | 'Y'));
+    let mut key = String::new();
+
+    for i in 0..chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).and_then(|p| chars.get(p));
+        let next = chars.get(i + 1);
+        let after = chars.get(i + 2);
+
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    key.push(c);
+                }
+            }
+            'B' => {
+                if !(prev == Some(&'M') && next.is_none()) {
+                    key.push('B');
+                }
+            }
+            'C' => {
+                if next == Some(&'H') && matches!(after, Some('R' | 'L')) {
+                    // Hard "ch", as in "chrome"
+                    key.push('K');
+                } else if next == Some(&'H') || (next == Some(&'I') && after == Some(&'A')) {
+                    key.push(if prev == Some(&'S') { 'K' } else { 'X' });
+                } else if is_front_vowel(next) {
+                    if prev != Some(&'S') {
+                        key.push('S');
+                    }
+                } else {
+                    key.push('K');
+                }
+            }
+            'D' => {
+                if next == Some(&'G') && is_front_vowel(after) {
+                    key.push('J');
+                } else {
+                    key.push('T');
+                }
+            }
+            'G' => {
+                if next == Some(&'H') && !is_vowel(after) {
+                    // Silent, as in "night"
+                } else if next == Some(&'N') && (after.is_none() || i + 2 == chars.len()) {
+                    // Silent, as in "sign"
+                } else if is_front_vowel(next) {
+                    key.push('J');
+                } else {
+                    key.push('K');
+                }
+            }
+            'H' => {
+                if is_vowel(next) && !matches!(prev, Some('C' | 'S' | 'P' | 'T' | 'G')) {
+                    key.push('H');
+                }
+            }
+            'K' => {
+                if prev != Some(&'C') {
+                    key.push('K');
+                }
+            }
+            'P' => key.push(if next == Some(&'H') { 'F' } else { 'P' }),
+            'Q' => key.push('K'),
+            'S' => {
+                if next == Some(&'H')
+                    || (next == Some(&'I') && matches!(after, Some('O' | 'A')))
+                {
+                    key.push('X');
+                } else {
+                    key.push('S');
+                }
+            }
+            'T' => {
+                if next == Some(&'I') && matches!(after, Some('O' | 'A')) {
+                    key.push('X');
+                } else if next == Some(&'H') {
+                    key.push('0');
+                } else if !(next == Some(&'C') && after == Some(&'H')) {
+                    key.push('T');
+                }
+            }
+            'V' => key.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    key.push(c);
+                }
+            }
+            'X' => key.push_str("KS"),
+            'Z' => key.push('S'),
+            _ => key.push(c),
+        }
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("open", "open"), 0);
+        assert_eq!(edit_distance("opne", "open"), 1);
+        assert_eq!(edit_distance("opn", "open"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_metaphone() {
+        assert_eq!(metaphone("chrome"), "KRM");
+        assert_eq!(metaphone("crome"), "KRM");
+        assert_eq!(metaphone("safari"), "SFR");
+        assert_eq!(metaphone("phone"), metaphone("fone"));
+        assert_eq!(metaphone("knight"), "NT");
+        assert_eq!(metaphone("thomas"), "0MS");
+    }
+
+    #[test]
+    fn test_best_match_joined() {
+        let m = best_match("fire fox", COMMON_APPS, &FuzzyConfig::default()).unwrap();
+        assert_eq!(m.candidate, "Firefox");
+        assert_eq!(m.method, MatchMethod::Joined);
+
+        let m = best_match("sa fari", COMMON_APPS, &FuzzyConfig::default()).unwrap();
+        assert_eq!(m.candidate, "Safari");
+    }
+
+    #[test]
+    fn test_best_match_edit_distance() {
+        let m = best_match("opne", &["open", "mute"], &FuzzyConfig::default()).unwrap();
+        assert_eq!(m.candidate, "open");
+        assert_eq!(m.method, MatchMethod::EditDistance);
+        assert_eq!(m.score, 0.75);
+    }
+
+    #[test]
+    fn test_best_match_phonetic() {
+        let config = FuzzyConfig {
+            threshold: 0.1,
+            ..FuzzyConfig::default()
+        };
+        let m = best_match("crome", &["chrome"], &config).unwrap();
+        assert_eq!(m.method, MatchMethod::Phonetic);
+
+        let no_phonetic = FuzzyConfig {
+            phonetic: false,
+            ..config
+        };
+        assert!(best_match("crome", &["chrome"], &no_phonetic).is_none());
+    }
+
+    #[test]
+    fn test_best_match_exact_or_distant() {
+        let config = FuzzyConfig::default();
+        assert!(best_match("safari", COMMON_APPS, &config).is_none());
+        assert!(best_match("photoshop", COMMON_APPS, &config).is_none());
+        assert!(best_match("opne", &["open"], &FuzzyConfig::disabled()).is_none());
+    }
+
+    #[test]
+    fn test_ranked_matches() {
+        let matches = ranked_matches("nots", COMMON_APPS, &FuzzyConfig::default());
+        let names: Vec<_> = matches.iter().map(|m| m.candidate).collect();
+        assert_eq!(names[0], "Notes");
+        assert!(matches.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+}
@@ -3,9 +3,20 @@
 //! Contains types and parsing logic for converting natural language
 //! commands into structured actions.
 
+pub mod fuzzy;
+pub mod number;
 pub mod parse;
+pub mod path;
 pub mod types;
+pub mod url;
+pub mod utterance;
 
 // Re-export commonly used items
-pub use parse::{parse_intent, ParseError};
-pub use types::Action;
+pub use number::{extract_number, NumberEntity};
+pub use fuzzy::{Correction, FuzzyConfig};
+pub use parse::{
+    parse_intent, parse_intent_ranked, parse_intent_with, Interpretation, ParseError, ParseOptions,
+};
+pub use types::{Action, TextTransform};
+pub use url::{classify_target, OpenTarget};
+pub use utterance::{parse_utterance, UtteranceStep};
@@ -0,0 +1,192 @@
+//! Number entity extraction for Luna.
+//!
+//! Speech recognizers rarely hand us bare digits: "forty two", "40%",
+//! "40 percent", "half" and "max" all mean a number. This module turns such
+//! phrases into values so every numeric slot (volume, brightness, ...)
+//! accepts the same spoken forms.
+
+/// A number extracted from a phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberEntity {
+    /// The numeric value
+    pub value: u32,
+    /// Whether the phrase was explicitly a percentage ("40%", "half", "max")
+    pub percent: bool,
+}
+
+/// Extract a number from a phrase that consists only of a number expression.
+///
+/// Accepts digits ("40"), percentages ("40%", "40 percent"), cardinal words
+/// ("forty two", "forty-two", "one hundred", "a hundred and five") and the
+/// words "zero", "half", "max"/"maximum"/"full" and "min"/"minimum".
+/// Returns `None` if any part of the phrase is not part of the number.
+///
+/// # Examples
+/// ```
+/// use luna::intent::number::extract_number;
+///
+/// let n = extract_number("forty two percent").unwrap();
+/// assert_eq!((n.value, n.percent), (42, true));
+/// ```
+pub fn extract_number(phrase: &str) -> Option<NumberEntity> {
+    let lowered = phrase.trim().to_lowercase();
+    let mut words: Vec<&str> = lowered
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Trailing percent marker: "40%", "40 %", "40 percent"
+    let mut percent = false;
+    if let Some(last) = words.last_mut() {
+        if let Some(number) = last.strip_suffix('%') {
+            percent = true;
+            if number.is_empty() {
+                words.pop();
+            } else {
+                *last = number;
+            }
+        } else if matches!(*last, "percent" | "pct") {
+            percent = true;
+            words.pop();
+        }
+    }
+    if words.len() >= 2 && words[words.len() - 2..] == ["per", "cent"] {
+        percent = true;
+        words.truncate(words.len() - 2);
+    }
+
+    match words.as_slice() {
+        [] => None,
+        [word] if word.chars().all(|c| c.is_ascii_digit()) => word
+            .parse()
+            .ok()
+            .map(|value| NumberEntity { value, percent }),
+        ["half"] | ["a", "half"] => Some(NumberEntity { value: 50, percent: true }),
+        ["max"] | ["maximum"] | ["full"] => Some(NumberEntity { value: 100, percent: true }),
+        ["min"] | ["minimum"] => Some(NumberEntity { value: 0, percent: true }),
+        _ => parse_cardinal(&words).map(|value| NumberEntity { value, percent }),
+    }
+}
+
+/// Parse spelled-out cardinal words ("one hundred and twenty three").
+fn parse_cardinal(words: &[&str]) -> Option<u32> {
+    let mut total: u32 = 0;
+    let mut current: u32 = 0;
+    let mut seen_number = false;
+
+    for (i, word) in words.iter().enumerate() {
+        match *word {
+            // "a hundred", "one hundred and five"
+            "a" if i == 0 => current = 1,
+            "and" if seen_number => {}
+            "hundred" => {
+                current = current.max(1).checked_mul(100)?;
+                seen_number = true;
+            }
+            "thousand" => {
+                total = total.checked_add(current.max(1).checked_mul(1000)?)?;
+                current = 0;
+                seen_number = true;
+            }
+            _ => {
+                current = current.checked_add(word_value(word)?)?;
+                seen_number = true;
+            }
+        }
+    }
+
+    if seen_number {
+        total.checked_add(current)
+    } else {
+        None
+    }
+}
+
+/// Value of a single cardinal word, or a run of digits.
+fn word_value(word: &str) -> Option<u32> {
+    let value = match word {
+        "zero" => 0,
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        "thirteen" => 13,
+        "fourteen" => 14,
+        "fifteen" => 15,
+        "sixteen" => 16,
+        "seventeen" => 17,
+        "eighteen" => 18,
+        "nineteen" => 19,
+        "twenty" => 20,
+        "thirty" => 30,
+        "forty" | "fourty" => 40,
+        "fifty" => 50,
+        "sixty" => 60,
+        "seventy" => 70,
+        "eighty" => 80,
+        "ninety" => 90,
+        _ if word.chars().all(|c| c.is_ascii_digit()) => return word.parse().ok(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(phrase: &str) -> Option<u32> {
+        extract_number(phrase).map(|n| n.value)
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(extract_number("40"), Some(NumberEntity { value: 40, percent: false }));
+        assert_eq!(value("0"), Some(0));
+    }
+
+    #[test]
+    fn test_percentages() {
+        let expected = Some(NumberEntity { value: 40, percent: true });
+        assert_eq!(extract_number("40%"), expected);
+        assert_eq!(extract_number("40 %"), expected);
+        assert_eq!(extract_number("40 percent"), expected);
+        assert_eq!(extract_number("forty per cent"), expected);
+    }
+
+    #[test]
+    fn test_cardinal_words() {
+        assert_eq!(value("zero"), Some(0));
+        assert_eq!(value("seven"), Some(7));
+        assert_eq!(value("forty two"), Some(42));
+        assert_eq!(value("forty-two"), Some(42));
+        assert_eq!(value("one hundred"), Some(100));
+        assert_eq!(value("a hundred and five"), Some(105));
+        assert_eq!(value("two thousand twenty six"), Some(2026));
+    }
+
+    #[test]
+    fn test_special_words() {
+        assert_eq!(extract_number("half"), Some(NumberEntity { value: 50, percent: true }));
+        assert_eq!(value("max"), Some(100));
+        assert_eq!(value("maximum"), Some(100));
+        assert_eq!(value("minimum"), Some(0));
+    }
+
+    #[test]
+    fn test_rejects_non_numbers() {
+        assert_eq!(value(""), None);
+        assert_eq!(value("abc"), None);
+        assert_eq!(value("forty apples"), None);
+        assert_eq!(value("percent"), None);
+        assert_eq!(value("and"), None);
+    }
+}
@@ -2,8 +2,32 @@
 //!
 //! Rule-based natural language parsing into Action types.
 
-use super::types::Action;
+use super::fuzzy::{ranked_matches, Correction, CorrectionKind, FuzzyConfig, COMMON_APPS};
+use super::number::extract_number;
+use super::path::{expand_path, looks_like_path, resolve_existing, well_known_folder};
+use super::types::{Action, TextTransform};
+use super::url::{classify_target, is_url, OpenTarget};
+use crate::catalog::{AppCatalog, Resolution};
+use crate::config::{AliasTable, SearchEngines, Snippets};
+use serde::Serialize;
+use std::path::PathBuf;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Volume change used for "turn it up", "louder", etc. when no amount is given.
+pub const DEFAULT_VOLUME_STEP: u8 = 10;
+
+/// Volume change used for "a bit louder", "a little quieter", etc.
+pub const SMALL_VOLUME_STEP: u8 = 5;
+
+/// Brightness change used for "brighter", "dim the screen", etc. when no amount is given.
+pub const DEFAULT_BRIGHTNESS_STEP: u8 = 10;
+
+/// Brightness change used for "a bit brighter", "a little dimmer", etc.
+pub const SMALL_BRIGHTNESS_STEP: u8 = 5;
+
+/// Title of notifications the user asks for ("notify me 'stand up'").
+pub const NOTIFICATION_TITLE: &str = "Luna";
 
 /// Errors that can occur during intent parsing.
 #[derive(Error, Debug)]
@@ -19,10 +43,161 @@ pub enum ParseError {
 
     #[error("Missing volume level. Try: 'set volume to 50'")]
     MissingVolumeLevel,
+
+    #[error("Invalid brightness level: {0}. Must be 0-100.")]
+    InvalidBrightness(String),
+
+    #[error("Missing brightness level. Try: 'set brightness to 70'")]
+    MissingBrightnessLevel,
+
+    #[error("Missing search query. Try: 'search for rust lifetimes'")]
+    MissingSearchQuery,
+
+    #[error("No such file or folder: {0}")]
+    PathNotFound(String),
+
+    #[error("Missing text to copy. Try: 'copy my email to the clipboard'")]
+    MissingCopyText,
+
+    #[error("Missing notification text. Try: 'notify me \"stand up\"'")]
+    MissingNotificationText,
+
+    #[error("No snippet named \"{0}\". Add it under [text] in snippets.toml")]
+    UnknownSnippet(String),
+}
+
+/// Command verbs that a misheard first word can be corrected to.
+const VERBS: &[&str] = &[
+    "open", "mute", "unmute", "set", "volume", "turn", "louder", "quieter", "search", "reveal", "quit",
+    "close", "hide", "switch", "brightness", "brighter", "dim",
+    "lock", "sleep", "restart", "reboot", "shutdown", "copy", "notify",
+];
+
+/// Options controlling how commands are interpreted.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Fuzzy matching for misheard verbs and app names
+    pub fuzzy: FuzzyConfig,
+    /// App names that spoken names are matched against
+    pub known_apps: Vec<String>,
+    /// User-defined spoken-name aliases, checked before any guessing
+    pub aliases: AliasTable,
+    /// Installed apps; spoken names resolve against these before fuzzy matching
+    pub catalog: AppCatalog,
+    /// Search engines that "search <engine> for ..." can name
+    pub search: SearchEngines,
+    /// Named text that "copy my <name>" puts on the clipboard
+    pub snippets: Snippets,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            fuzzy: FuzzyConfig::default(),
+            known_apps: COMMON_APPS.iter().map(|app| app.to_string()).collect(),
+            aliases: AliasTable::default(),
+            catalog: AppCatalog::default(),
+            search: SearchEngines::default(),
+            snippets: Snippets::default(),
+        }
+    }
 }
 
+/// Confidence of an app name that matched no known app and was only capitalized.
+pub const UNKNOWN_APP_CONFIDENCE: f64 = 0.5;
+
+/// Confidence of each installed app when a spoken name matches several.
+pub const AMBIGUOUS_APP_CONFIDENCE: f64 = 0.6;
+
+/// Confidence of each reading of an open target that could be a URL or an
+/// app ("notes.app").
+pub const AMBIGUOUS_TARGET_CONFIDENCE: f64 = 0.6;
+
+/// Confidence below which a caller should confirm before executing.
+pub const CONFIRMATION_THRESHOLD: f64 = 0.75;
+
+/// Default number of candidates returned by `parse_intent_ranked`.
+pub const DEFAULT_CANDIDATES: usize = 3;
+
+/// One example command per kind of action, in the order `Action` declares them.
+pub const ACTION_EXAMPLES: &[&str] = &[
+    "open safari",
+    "quit slack",
+    "force quit zoom",
+    "hide mail",
+    "switch to terminal",
+    "open github.com",
+    "open /tmp",
+    "search for rust traits",
+    "set volume to 40",
+    "turn the volume up",
+    "mute",
+    "unmute",
+    "set brightness to 70",
+    "brightness down",
+    "play",
+    "pause",
+    "play pause",
+    "next track",
+    "previous track",
+    "what's playing",
+    "lock the screen",
+    "sleep",
+    "restart",
+    "shut down",
+    "log out",
+    "copy hello world",
+    "what's on my clipboard",
+    "make the clipboard lowercase",
+    "notify me to stand up",
+];
+
+/// A parsed action together with how it was reached.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Interpretation {
+    pub action: Action,
+    /// How sure the parser is of this action (0.0-1.0)
+    pub confidence: f64,
+    /// Name of the parsing rule that produced the action
+    #[schema(value_type = String)]
+    pub rule: &'static str,
+    /// Corrections applied to the input to reach this action
+    pub corrections: Vec<Correction>,
+}
+
+impl Interpretation {
+    /// Whether the confidence is low enough that the user should confirm.
+    pub fn needs_confirmation(&self) -> bool {
+        self.confidence < CONFIRMATION_THRESHOLD
+    }
+}
+
+/// A parsing rule: returns `None` if the input is not for this rule.
+type Rule = fn(&str, &ParseOptions) -> Option<Result<Action, ParseError>>;
+
+/// Parsing rules in priority order.
+const RULES: &[(&str, Rule)] = &[
+    ("mute", |input, _| try_parse_mute(input).map(Ok)),
+    ("unmute", |input, _| try_parse_unmute(input).map(Ok)),
+    ("volume_adjust", |input, _| try_parse_volume_adjust(input)),
+    ("set_volume", |input, _| try_parse_volume(input)),
+    ("brightness_adjust", |input, _| try_parse_brightness_adjust(input)),
+    ("set_brightness", |input, _| try_parse_brightness(input)),
+    ("media", |input, _| try_parse_media(input).map(Ok)),
+    ("power", |input, _| try_parse_power(input).map(Ok)),
+    ("clipboard", try_parse_clipboard),
+    ("notify", |input, _| try_parse_notify(input)),
+    ("app_control", try_parse_app_control),
+    ("open", try_parse_open),
+    ("search", try_parse_search),
+    ("reveal", try_parse_reveal),
+];
+
 /// Parse a natural language command into an Action.
 ///
+/// Uses the default `ParseOptions`; see `parse_intent_with` to tune fuzzy
+/// matching or to find out which corrections were applied.
+///
 /// # Arguments
 /// * `input` - Raw natural language input from the user
 ///
@@ -37,28 +212,191 @@ pub enum ParseError {
 /// let action = parse_intent("open safari").unwrap();
 /// ```
 pub fn parse_intent(input: &str) -> Result<Action, ParseError> {
+    parse_intent_with(input, &ParseOptions::default()).map(|interpretation| interpretation.action)
+}
+
+/// Parse a natural language command, applying fuzzy corrections.
+///
+/// Returns the most confident candidate from `parse_intent_ranked`. A
+/// misheard first word is matched against the known command verbs ("opne" ->
+/// "open") and app names against `options.known_apps` ("fire fox" ->
+/// "Firefox"); every correction applied is reported in the result.
+pub fn parse_intent_with(input: &str, options: &ParseOptions) -> Result<Interpretation, ParseError> {
+    let mut candidates = parse_intent_ranked(input, options, 1)?;
+    Ok(candidates.remove(0))
+}
+
+/// Parse a natural language command into its `limit` most likely actions.
+///
+/// Every rule is tried against the input and against fuzzy corrections of
+/// its first word; app names are matched against `options.known_apps`.
+/// Each candidate's confidence is the product of the similarity scores of
+/// the corrections it needed (1.0 for an exact match), or
+/// `UNKNOWN_APP_CONFIDENCE` for an app name that matched nothing.
+///
+/// # Returns
+/// * `Ok(candidates)` - At least one candidate, most confident first
+/// * `Err(ParseError)` - No rule produced an action
+pub fn parse_intent_ranked(
+    input: &str,
+    options: &ParseOptions,
+    limit: usize,
+) -> Result<Vec<Interpretation>, ParseError> {
     // Normalize input: lowercase, trim, collapse whitespace
     let normalized = normalize_input(input);
 
-    // Try each parsing rule in order
-    if let Some(action) = try_parse_mute(&normalized) {
-        return Ok(action);
+    let mut variants = vec![(normalized.clone(), Vec::new(), 1.0)];
+    variants.extend(verb_corrections(&normalized, &options.fuzzy));
+
+    let mut candidates: Vec<Interpretation> = Vec::new();
+    let mut first_error: Option<ParseError> = None;
+
+    for (text, corrections, confidence) in variants {
+        for (rule, parse) in RULES {
+            match parse(&text, options) {
+                Some(Ok(action)) => {
+                    for (action, app_correction, score) in app_alternatives(action, options) {
+                        let mut corrections = corrections.clone();
+                        corrections.extend(app_correction);
+                        candidates.push(Interpretation {
+                            action,
+                            confidence: confidence * score,
+                            rule,
+                            corrections,
+                        });
+                    }
+                }
+                Some(Err(error)) => {
+                    first_error.get_or_insert(error);
+                }
+                None => {}
+            }
+        }
+    }
+
+    if candidates.is_empty() {
+        return Err(first_error.unwrap_or_else(|| ParseError::UnrecognizedCommand(input.to_string())));
+    }
+
+    // Most confident first; the stable sort keeps rule order for ties
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut seen: Vec<Action> = Vec::new();
+    candidates.retain(|candidate| {
+        if seen.contains(&candidate.action) {
+            false
+        } else {
+            seen.push(candidate.action.clone());
+            true
+        }
+    });
+    candidates.truncate(limit.max(1));
+
+    Ok(candidates)
+}
+
+/// Corrections of a misheard first word to command verbs, best first.
+fn verb_corrections(normalized: &str, config: &FuzzyConfig) -> Vec<(String, Vec<Correction>, f64)> {
+    let (first, rest) = match normalized.split_once(' ') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (normalized, None),
+    };
+
+    ranked_matches(first, VERBS, config)
+        .into_iter()
+        .map(|found| {
+            let corrected = match rest {
+                Some(rest) => format!("{} {}", found.candidate, rest),
+                None => found.candidate.to_string(),
+            };
+            let correction = Correction {
+                kind: CorrectionKind::Verb,
+                from: first.to_string(),
+                to: found.candidate.to_string(),
+                method: found.method,
+                score: found.score,
+            };
+            (corrected, vec![correction], found.score)
+        })
+        .collect()
+}
+
+/// Possible readings of an action's app name, each with its correction and score.
+///
+/// Non-app actions and alias targets are returned unchanged with score 1.0.
+/// An open target that could also be a website ("notes.app") yields both
+/// readings, scaled by `AMBIGUOUS_TARGET_CONFIDENCE`.
+fn app_alternatives(action: Action, options: &ParseOptions) -> Vec<(Action, Option<Correction>, f64)> {
+    let Some(name) = action.app_name().map(str::to_string) else {
+        return vec![(action, None, 1.0)];
+    };
+
+    if options.aliases.is_target(&name) {
+        return vec![(action, None, 1.0)];
     }
 
-    if let Some(action) = try_parse_unmute(&normalized) {
-        return Ok(action);
+    let readings = |name: String, scale: f64| {
+        app_name_alternatives(name, options)
+            .into_iter()
+            .map(|(name, correction, score)| (action.clone().with_app_name(name), correction, score * scale))
+            .collect::<Vec<_>>()
+    };
+
+    if let (Action::OpenApp { .. }, OpenTarget::Ambiguous(url)) = (&action, classify_target(&name)) {
+        let app_name = name.strip_suffix(".app").unwrap_or(&name).to_string();
+        let mut alternatives = readings(app_name, AMBIGUOUS_TARGET_CONFIDENCE);
+        alternatives.push((
+            Action::OpenUrl {
+                url: url.to_lowercase(),
+            },
+            None,
+            AMBIGUOUS_TARGET_CONFIDENCE,
+        ));
+        return alternatives;
     }
 
-    if let Some(result) = try_parse_volume(&normalized) {
-        return result;
+    readings(name, 1.0)
+}
+
+/// Possible apps for a spoken name.
+///
+/// Known app names are returned unchanged with score 1.0. A name matching
+/// one installed app becomes that app's canonical name; one matching several
+/// yields each of them at `AMBIGUOUS_APP_CONFIDENCE`.
+fn app_name_alternatives(name: String, options: &ParseOptions) -> Vec<(String, Option<Correction>, f64)> {
+    match options.catalog.resolve(&name) {
+        Resolution::Found(app) => return vec![(app.name.clone(), None, 1.0)],
+        Resolution::Ambiguous(apps) => {
+            return apps
+                .into_iter()
+                .map(|app| (app.name.clone(), None, AMBIGUOUS_APP_CONFIDENCE))
+                .collect()
+        }
+        Resolution::NotFound => {}
     }
 
-    if let Some(result) = try_parse_open(&normalized) {
-        return result;
+    if let Some(known) = options
+        .known_apps
+        .iter()
+        .find(|app| app.eq_ignore_ascii_case(&name))
+    {
+        return vec![(known.clone(), None, 1.0)];
     }
 
-    // No rule matched
-    Err(ParseError::UnrecognizedCommand(input.to_string()))
+    let mut alternatives: Vec<_> = ranked_matches(&name, &options.known_apps, &options.fuzzy)
+        .into_iter()
+        .map(|found| {
+            let correction = Correction {
+                kind: CorrectionKind::AppName,
+                from: name.to_lowercase(),
+                to: found.candidate.to_string(),
+                method: found.method,
+                score: found.score,
+            };
+            (found.candidate.to_string(), Some(correction), found.score)
+        })
+        .collect();
+    alternatives.push((name, None, UNKNOWN_APP_CONFIDENCE));
+    alternatives
 }
 
 /// Normalize input for consistent parsing.
@@ -93,56 +431,218 @@ fn try_parse_unmute(input: &str) -> Option<Action> {
 fn try_parse_volume(input: &str) -> Option<Result<Action, ParseError>> {
     // Match patterns like "set volume to 40" or "volume 40"
     let patterns = [
-        "set volume to ",
-        "set volume ",
-        "volume to ",
-        "volume ",
+        "set volume to",
+        "set volume",
+        "volume to",
+        "volume",
     ];
 
     for pattern in patterns {
-        if let Some(rest) = input.strip_prefix(pattern) {
+        if let Some(rest) = strip_words(input, pattern) {
             let level_str = rest.trim();
             if level_str.is_empty() {
                 return Some(Err(ParseError::MissingVolumeLevel));
             }
 
-            match level_str.parse::<u8>() {
-                Ok(level) if level <= 100 => {
-                    return Some(Ok(Action::SetVolume { level }));
-                }
-                Ok(level) => {
-                    return Some(Err(ParseError::InvalidVolume(format!(
-                        "{} (exceeds 100)",
-                        level
-                    ))));
-                }
-                Err(_) => {
-                    return Some(Err(ParseError::InvalidVolume(level_str.to_string())));
-                }
+            return Some(
+                parse_level(level_str)
+                    .map(|level| Action::SetVolume { level })
+                    .map_err(ParseError::InvalidVolume),
+            );
+        }
+    }
+
+    None
+}
+
+/// Try to parse "set brightness to <level>" command.
+fn try_parse_brightness(input: &str) -> Option<Result<Action, ParseError>> {
+    // Match patterns like "set the screen brightness to 40" or "brightness 40"
+    let patterns = [
+        "set brightness to",
+        "set brightness",
+        "set the brightness to",
+        "set the brightness",
+        "set screen brightness to",
+        "set the screen brightness to",
+        "brightness to",
+        "brightness",
+    ];
+
+    for pattern in patterns {
+        if let Some(rest) = strip_words(input, pattern) {
+            let level_str = rest.trim();
+            if level_str.is_empty() {
+                return Some(Err(ParseError::MissingBrightnessLevel));
             }
+
+            return Some(
+                parse_level(level_str)
+                    .map(|level| Action::SetBrightness { level })
+                    .map_err(ParseError::InvalidBrightness),
+            );
         }
     }
 
     None
 }
 
+/// Strip `prefix` from `input` if it ends on a word boundary.
+fn strip_words<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    input
+        .strip_prefix(prefix)
+        .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+/// Parse a 0-100 level ("40", "forty percent", "half"); the error describes
+/// the invalid level.
+fn parse_level(level_str: &str) -> Result<u8, String> {
+    match extract_number(level_str).map(|n| n.value) {
+        Some(level) if level <= 100 => Ok(level as u8),
+        Some(level) => Err(format!("{} (exceeds 100)", level)),
+        None => Err(level_str.to_string()),
+    }
+}
+
+/// Try to parse a relative volume command.
+///
+/// Accepts phrasings like "turn it up", "volume down by 10", "louder",
+/// "a bit quieter" or "turn the volume up 20". Every word must belong to the
+/// volume vocabulary so unrelated commands containing "up"/"down" don't match.
+fn try_parse_volume_adjust(input: &str) -> Option<Result<Action, ParseError>> {
+    const VOCABULARY: AdjustVocabulary = AdjustVocabulary {
+        filler: &[
+            "turn", "it", "the", "volume", "sound", "make", "a", "bit", "little", "by", "please",
+        ],
+        up: &["up", "louder", "raise", "increase"],
+        down: &["down", "quieter", "softer", "lower", "decrease"],
+    };
+
+    let delta = parse_adjustment(input, &VOCABULARY, DEFAULT_VOLUME_STEP, SMALL_VOLUME_STEP)?;
+    Some(
+        delta
+            .map(|delta| Action::AdjustVolume { delta })
+            .map_err(ParseError::InvalidVolume),
+    )
+}
+
+/// Try to parse a relative brightness command.
+///
+/// Accepts phrasings like "brighter", "dim the screen", "brightness down by
+/// 20" or "make the display a bit brighter". The command must mention the
+/// screen or brightness, so "turn it up" stays a volume command.
+fn try_parse_brightness_adjust(input: &str) -> Option<Result<Action, ParseError>> {
+    const VOCABULARY: AdjustVocabulary = AdjustVocabulary {
+        filler: &[
+            "turn", "it", "the", "brightness", "screen", "display", "make", "a", "bit", "little",
+            "by", "please",
+        ],
+        up: &["up", "brighter", "brighten", "raise", "increase"],
+        down: &["down", "dimmer", "dim", "darker", "lower", "decrease"],
+    };
+    const BRIGHTNESS_WORDS: &[&str] = &[
+        "brightness", "screen", "display", "brighter", "brighten", "dimmer", "dim", "darker",
+    ];
+
+    if !input.split_whitespace().any(|word| BRIGHTNESS_WORDS.contains(&word)) {
+        return None;
+    }
+
+    let delta = parse_adjustment(input, &VOCABULARY, DEFAULT_BRIGHTNESS_STEP, SMALL_BRIGHTNESS_STEP)?;
+    Some(
+        delta
+            .map(|delta| Action::AdjustBrightness { delta })
+            .map_err(ParseError::InvalidBrightness),
+    )
+}
+
+/// Words understood by a relative level command ("turn the volume up by 10").
+struct AdjustVocabulary {
+    /// Words that carry no meaning of their own
+    filler: &'static [&'static str],
+    /// Words that raise the level
+    up: &'static [&'static str],
+    /// Words that lower the level
+    down: &'static [&'static str],
+}
+
+/// Parse a relative level command into a signed change.
+///
+/// Every word must belong to `vocabulary` apart from a single amount; with no
+/// amount, "a bit"/"a little" selects `small_step` and anything else
+/// `default_step`. The error describes an out-of-range amount.
+fn parse_adjustment(
+    input: &str,
+    vocabulary: &AdjustVocabulary,
+    default_step: u8,
+    small_step: u8,
+) -> Option<Result<i8, String>> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    // Everything outside the vocabulary must form a single number ("by forty percent")
+    let mut sign: Option<i8> = None;
+    let mut amount_words: Vec<&str> = Vec::new();
+    for word in &words {
+        if vocabulary.up.contains(word) {
+            sign = Some(1);
+        } else if vocabulary.down.contains(word) {
+            sign = Some(-1);
+        } else if !vocabulary.filler.contains(word) {
+            amount_words.push(word);
+        }
+    }
+    let sign = sign?;
+
+    let step = if amount_words.is_empty() {
+        if words.contains(&"bit") || words.contains(&"little") {
+            small_step
+        } else {
+            default_step
+        }
+    } else {
+        let amount = amount_words.join(" ");
+        match extract_number(&amount)?.value {
+            step if step <= 100 => step as u8,
+            step => return Some(Err(format!("{} (exceeds 100)", step))),
+        }
+    };
+
+    Some(Ok(sign * step as i8))
+}
+
 /// Try to parse "open <target>" command.
-fn try_parse_open(input: &str) -> Option<Result<Action, ParseError>> {
+///
+/// User aliases are checked before the target is treated as a URL or an
+/// app name to capitalize.
+fn try_parse_open(input: &str, options: &ParseOptions) -> Option<Result<Action, ParseError>> {
     if let Some(rest) = input.strip_prefix("open ") {
         let target = rest.trim();
         if target.is_empty() {
             return Some(Err(ParseError::MissingOpenTarget));
         }
 
-        // Check if it's a URL
-        if is_url(target) {
-            // Preserve original case for URLs
-            return Some(Ok(Action::OpenUrl {
-                url: target.to_string(),
+        if let Some(aliased) = options.aliases.resolve(target) {
+            return Some(Ok(match classify_target(aliased) {
+                OpenTarget::Url(url) => Action::OpenUrl { url },
+                _ => Action::OpenApp {
+                    name: aliased.to_string(),
+                },
             }));
         }
 
-        // It's an app name - capitalize first letter of each word
+        // "open downloads", "open ~/notes/todo.md in finder"
+        let (path, reveal) = strip_reveal_suffix(target);
+        if let Some(result) = parse_path_target(path, options, reveal) {
+            return Some(result);
+        }
+
+        if let OpenTarget::Url(url) = classify_target(target) {
+            return Some(Ok(Action::OpenUrl { url }));
+        }
+
+        // It's an app name - capitalize first letter of each word. An
+        // ambiguous target ("notes.app") gets its URL reading in
+        // `app_alternatives`
         let app_name = capitalize_app_name(target);
         return Some(Ok(Action::OpenApp { name: app_name }));
     }
@@ -150,12 +650,348 @@ fn try_parse_open(input: &str) -> Option<Result<Action, ParseError>> {
     None
 }
 
-/// Check if the target looks like a URL.
-fn is_url(target: &str) -> bool {
-    target.starts_with("http://")
-        || target.starts_with("https://")
-        || target.contains("://")
-        || (target.contains('.') && !target.contains(' '))
+/// Builds an app action from an app name.
+type AppAction = fn(String) -> Action;
+
+/// Questions asking what is playing.
+const NOW_PLAYING_PHRASES: &[&str] = &[
+    "what's playing",
+    "what is playing",
+    "what's this song",
+    "what is this song",
+    "what song is this",
+    "what song is playing",
+    "now playing",
+];
+
+/// Words that don't change a media command ("pause the music" -> "pause").
+const MEDIA_FILLER: &[&str] = &["the", "music", "song", "track", "playback", "this", "media"];
+
+/// Try to parse a media transport command: "pause", "play music", "next
+/// track", "previous song", "what's playing".
+fn try_parse_media(input: &str) -> Option<Action> {
+    let input = input.trim_end_matches(['?', '.', '!']);
+    if NOW_PLAYING_PHRASES.contains(&input) {
+        return Some(Action::NowPlaying);
+    }
+
+    let words: Vec<&str> = input
+        .split_whitespace()
+        .filter(|word| !MEDIA_FILLER.contains(word))
+        .collect();
+
+    match words.join(" ").as_str() {
+        "play" | "resume" | "unpause" | "continue" => Some(Action::Play),
+        "pause" | "stop" => Some(Action::Pause),
+        "play pause" | "toggle" => Some(Action::TogglePlayback),
+        "next" | "skip" | "next one" => Some(Action::NextTrack),
+        "previous" | "last" | "go back" | "back" => Some(Action::PreviousTrack),
+        _ => None,
+    }
+}
+
+/// Words that don't change a power or session command ("restart the computer" -> "restart").
+const POWER_FILLER: &[&str] = &[
+    "the", "my", "this", "computer", "mac", "laptop", "pc", "machine", "system", "now", "please",
+];
+
+/// Try to parse a power or session command: "lock the screen", "go to
+/// sleep", "restart", "shut down the computer", "log out".
+fn try_parse_power(input: &str) -> Option<Action> {
+    let input = input.trim_end_matches(['.', '!']);
+    let words: Vec<&str> = input
+        .split_whitespace()
+        .filter(|word| !POWER_FILLER.contains(word))
+        .collect();
+
+    match words.join(" ").as_str() {
+        "lock" | "lock screen" | "lock up" => Some(Action::LockScreen),
+        "sleep" | "go to sleep" | "put to sleep" | "suspend" => Some(Action::Sleep),
+        "restart" | "reboot" => Some(Action::Restart),
+        "shut down" | "shutdown" | "power off" | "turn off" => Some(Action::Shutdown),
+        "log out" | "logout" | "log off" | "sign out" => Some(Action::LogOut),
+        _ => None,
+    }
+}
+
+/// Questions asking what is on the clipboard, after "my"/"the" are removed.
+const READ_CLIPBOARD_PHRASES: &[&str] = &[
+    "what's on clipboard",
+    "what is on clipboard",
+    "what's in clipboard",
+    "what is in clipboard",
+    "read clipboard",
+    "show clipboard",
+    "paste clipboard",
+];
+
+/// Spoken names of clipboard transforms.
+const TEXT_TRANSFORMS: &[(&[&str], TextTransform)] = &[
+    (&["lowercase", "lower case"], TextTransform::Lowercase),
+    (&["uppercase", "upper case", "all caps", "caps"], TextTransform::Uppercase),
+    (&["title case", "titlecase"], TextTransform::TitleCase),
+    (&["trim", "trim whitespace", "trim spaces"], TextTransform::Trim),
+    (
+        &["strip formatting", "remove formatting", "clear formatting", "plain text"],
+        TextTransform::StripFormatting,
+    ),
+    (&["url decode", "urldecode", "decode url", "decode"], TextTransform::UrlDecode),
+];
+
+/// Words around a transform name ("make the clipboard lowercase",
+/// "strip formatting from my clipboard").
+const TRANSFORM_FILLER: &[&str] = &[
+    "make", "convert", "change", "turn", "the", "my", "clipboard", "text", "to", "into", "from", "on",
+    "in", "of", "please",
+];
+
+/// Try to parse a clipboard command: "copy my email to the clipboard",
+/// "what's on my clipboard", "make the clipboard lowercase".
+fn try_parse_clipboard(input: &str, options: &ParseOptions) -> Option<Result<Action, ParseError>> {
+    let input = input.trim_end_matches(['?', '.', '!']);
+
+    if let Some(rest) = strip_words(input, "copy") {
+        return Some(parse_copy_target(rest.trim(), options));
+    }
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    if !words.contains(&"clipboard") {
+        return None;
+    }
+
+    let question: Vec<&str> = words.iter().copied().filter(|word| !matches!(*word, "my" | "the")).collect();
+    if READ_CLIPBOARD_PHRASES.contains(&question.join(" ").as_str()) {
+        return Some(Ok(Action::ReadClipboard));
+    }
+
+    let name: Vec<&str> = words.into_iter().filter(|word| !TRANSFORM_FILLER.contains(word)).collect();
+    let name = name.join(" ");
+    TEXT_TRANSFORMS
+        .iter()
+        .find(|(names, _)| names.contains(&name.as_str()))
+        .map(|(_, transform)| Ok(Action::TransformClipboard { transform: *transform }))
+}
+
+/// Work out the text for "copy <target> [to the clipboard]".
+///
+/// "my <name>" must be a snippet; any other target is a snippet if one has
+/// that name, otherwise it is copied as spoken.
+fn parse_copy_target(target: &str, options: &ParseOptions) -> Result<Action, ParseError> {
+    let target = ["to the clipboard", "to my clipboard", "to clipboard"]
+        .iter()
+        .find_map(|suffix| target.strip_suffix(suffix))
+        .unwrap_or(target)
+        .trim();
+    if target.is_empty() {
+        return Err(ParseError::MissingCopyText);
+    }
+
+    if let Some(name) = strip_words(target, "my") {
+        let name = name.trim();
+        return match options.snippets.get(name) {
+            Some(text) => Ok(Action::CopyText { text: text.to_string() }),
+            None => Err(ParseError::UnknownSnippet(name.to_string())),
+        };
+    }
+
+    let text = options.snippets.get(target).unwrap_or(target);
+    Ok(Action::CopyText { text: text.to_string() })
+}
+
+/// Ways of asking for a notification, longest first.
+const NOTIFY_PREFIXES: &[&str] = &[
+    "send me a notification",
+    "send a notification",
+    "show me a notification",
+    "show a notification",
+    "notify me",
+    "notify",
+];
+
+/// Try to parse "notify me 'stand up'" / "send a notification saying ...".
+fn try_parse_notify(input: &str) -> Option<Result<Action, ParseError>> {
+    let rest = NOTIFY_PREFIXES.iter().find_map(|prefix| strip_words(input, prefix))?;
+
+    let rest = rest.trim().trim_start_matches(':').trim_start();
+    let body = ["to ", "that ", "saying ", "with ", "about "]
+        .iter()
+        .find_map(|connector| rest.strip_prefix(connector))
+        .unwrap_or(rest)
+        .trim()
+        .trim_matches(['"', '\'', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}'])
+        .trim();
+    if body.is_empty() {
+        return Some(Err(ParseError::MissingNotificationText));
+    }
+
+    Some(Ok(Action::Notify {
+        title: NOTIFICATION_TITLE.to_string(),
+        body: body.to_string(),
+    }))
+}
+
+/// Verbs that act on a running app, with the action each one builds.
+const APP_CONTROL_VERBS: &[(&str, AppAction)] = &[
+    ("force quit ", |name| Action::ForceQuitApp { name }),
+    ("force close ", |name| Action::ForceQuitApp { name }),
+    ("kill ", |name| Action::ForceQuitApp { name }),
+    ("quit ", |name| Action::QuitApp { name }),
+    ("close ", |name| Action::QuitApp { name }),
+    ("exit ", |name| Action::QuitApp { name }),
+    ("hide ", |name| Action::HideApp { name }),
+    ("minimize ", |name| Action::HideApp { name }),
+    ("switch to ", |name| Action::FocusApp { name }),
+    ("focus ", |name| Action::FocusApp { name }),
+    ("bring up ", |name| Action::FocusApp { name }),
+];
+
+/// Try to parse "quit slack", "kill zoom", "hide mail", "switch to chrome".
+fn try_parse_app_control(input: &str, options: &ParseOptions) -> Option<Result<Action, ParseError>> {
+    let (rest, build) = APP_CONTROL_VERBS
+        .iter()
+        .find_map(|(verb, build)| Some((input.strip_prefix(verb)?, build)))?;
+
+    let target = rest.trim();
+    let target = target.strip_prefix("the ").unwrap_or(target);
+    let target = target.strip_suffix(" app").unwrap_or(target);
+    if target.is_empty() {
+        return None;
+    }
+
+    // Aliases apply, but an alias for a website isn't an app
+    let name = match options.aliases.resolve(target) {
+        Some(aliased) if !is_url(aliased) => aliased.to_string(),
+        _ => capitalize_app_name(target),
+    };
+    Some(Ok(build(name)))
+}
+
+/// Suffixes asking for the file manager instead of the default app.
+const REVEAL_SUFFIXES: &[&str] = &[
+    " in the finder",
+    " in finder",
+    " in the file manager",
+    " in file manager",
+    " in files",
+];
+
+/// Try to parse "reveal <path>" or "show <path> in finder".
+fn try_parse_reveal(input: &str, options: &ParseOptions) -> Option<Result<Action, ParseError>> {
+    let (target, in_file_manager) = if let Some(rest) = input.strip_prefix("reveal ") {
+        (strip_reveal_suffix(rest).0, true)
+    } else {
+        strip_reveal_suffix(input.strip_prefix("show ")?)
+    };
+
+    if !in_file_manager {
+        return None;
+    }
+    parse_path_target(target, options, true)
+}
+
+/// Split off a trailing "in finder" / "in the file manager".
+fn strip_reveal_suffix(target: &str) -> (&str, bool) {
+    REVEAL_SUFFIXES
+        .iter()
+        .find_map(|suffix| target.strip_suffix(suffix))
+        .map_or((target, false), |rest| (rest.trim(), true))
+}
+
+/// Parse an open target that names a place on disk.
+///
+/// Explicit paths are expanded ("~", "$VAR") and well-known folders looked
+/// up ("downloads", "my documents folder"). A bare folder name that is also
+/// an app ("music") is left to the app reading. Returns `None` if the target
+/// is not a path, and `ParseError::PathNotFound` if it doesn't exist.
+fn parse_path_target(target: &str, options: &ParseOptions, reveal: bool) -> Option<Result<Action, ParseError>> {
+    let open = |path: PathBuf| match resolve_existing(&path) {
+        Some(path) => Ok(Action::OpenPath {
+            path: path.to_string_lossy().to_string(),
+            reveal,
+        }),
+        None => Err(ParseError::PathNotFound(path.display().to_string())),
+    };
+
+    if looks_like_path(target) {
+        return Some(open(expand_path(target)));
+    }
+
+    let name = ["my ", "the "]
+        .iter()
+        .find_map(|prefix| target.strip_prefix(prefix))
+        .unwrap_or(target);
+    let name = [" folder", " directory"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name);
+    let folder = well_known_folder(name)?;
+
+    let qualified = name != target;
+    let is_app = options.known_apps.iter().any(|app| app.eq_ignore_ascii_case(name))
+        || options.catalog.get(name).is_some();
+    if is_app && !qualified && !reveal {
+        return None;
+    }
+    Some(open(folder))
+}
+
+/// Ways of asking for a web search, longest first.
+const SEARCH_PREFIXES: &[&str] = &[
+    "search the web for ",
+    "search the internet for ",
+    "search online for ",
+    "search for ",
+    "look up ",
+    "search ",
+];
+
+/// Try to parse a web search.
+///
+/// Accepts "search for <query>", "look up <query>", "search <engine> for
+/// <query>", "search for <query> on <engine>" and "<engine> <query>"
+/// ("google best pizza near me"). Engines are looked up in
+/// `options.search`; without one the configured default is used.
+fn try_parse_search(input: &str, options: &ParseOptions) -> Option<Result<Action, ParseError>> {
+    let engines = &options.search;
+    let search = |engine: &str, query: &str| {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(ParseError::MissingSearchQuery);
+        }
+        Ok(Action::WebSearch {
+            query: query.to_string(),
+            engine: engine.to_string(),
+        })
+    };
+
+    if SEARCH_PREFIXES.iter().any(|prefix| input == prefix.trim_end()) {
+        return Some(Err(ParseError::MissingSearchQuery));
+    }
+
+    if let Some(rest) = SEARCH_PREFIXES.iter().find_map(|p| input.strip_prefix(p)) {
+        // "search github for clap"
+        if let Some((engine, query)) = rest
+            .split_once(" for ")
+            .and_then(|(engine, query)| Some((engines.get(engine)?.0, query)))
+        {
+            return Some(search(engine, query));
+        }
+
+        // "search for cats on youtube"
+        if let Some((query, engine)) = rest
+            .rsplit_once(" on ")
+            .and_then(|(query, engine)| Some((query, engines.get(engine)?.0)))
+        {
+            return Some(search(engine, query));
+        }
+
+        return Some(search(&engines.default, rest));
+    }
+
+    // "google best pizza near me"
+    let (first, query) = input.split_once(' ')?;
+    let (engine, _) = engines.get(first)?;
+    Some(search(engine, query.strip_prefix("for ").unwrap_or(query)))
 }
 
 /// Capitalize the first letter of each word for app names.
@@ -175,6 +1011,8 @@ fn capitalize_app_name(name: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::catalog::AppEntry;
+    use crate::intent::fuzzy::MatchMethod;
 
     #[test]
     fn test_parse_open_app() {
@@ -200,6 +1038,190 @@ mod tests {
         assert_eq!(action, Action::OpenUrl { url: "http://example.com".to_string() });
     }
 
+    #[test]
+    fn test_parse_open_bare_domain() {
+        let action = parse_intent("open github.com").unwrap();
+        assert_eq!(action, Action::OpenUrl { url: "https://github.com".to_string() });
+        let action = parse_intent("open localhost:3000").unwrap();
+        assert_eq!(action, Action::OpenUrl { url: "http://localhost:3000".to_string() });
+    }
+
+    #[test]
+    fn test_parse_open_dotted_app_name() {
+        let action = parse_intent("open node.js").unwrap();
+        assert_eq!(action, Action::OpenApp { name: "Node.js".to_string() });
+    }
+
+    #[test]
+    fn test_ranked_ambiguous_target() {
+        let candidates = parse_intent_ranked("open notes.app", &ParseOptions::default(), 3).unwrap();
+        assert_eq!(candidates[0].action, Action::OpenApp { name: "Notes".to_string() });
+        assert_eq!(candidates[1].action, Action::OpenUrl { url: "https://notes.app".to_string() });
+        assert!(candidates.iter().all(|c| c.needs_confirmation()));
+    }
+
+    #[test]
+    fn test_parse_open_path() {
+        let dir = std::env::temp_dir().join(format!("luna-open-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.to_string_lossy().to_string();
+
+        assert_eq!(
+            parse_intent(&format!("open {}", path)).unwrap(),
+            Action::OpenPath { path: path.clone(), reveal: false }
+        );
+        assert_eq!(
+            parse_intent(&format!("open {} in finder", path)).unwrap(),
+            Action::OpenPath { path: path.clone(), reveal: true }
+        );
+        assert_eq!(
+            parse_intent(&format!("reveal {}", path)).unwrap(),
+            Action::OpenPath { path: path.clone(), reveal: true }
+        );
+        assert_eq!(
+            parse_intent(&format!("show {} in the file manager", path)).unwrap(),
+            Action::OpenPath { path, reveal: true }
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_open_missing_path() {
+        assert!(matches!(
+            parse_intent("open /definitely/not/here.txt"),
+            Err(ParseError::PathNotFound(path)) if path == "/definitely/not/here.txt"
+        ));
+    }
+
+    #[test]
+    fn test_parse_open_folder_name_that_is_an_app() {
+        assert_eq!(parse_intent("open music").unwrap(), Action::OpenApp { name: "Music".to_string() });
+    }
+
+    #[test]
+    fn test_parse_app_control() {
+        let name = |name: &str| name.to_string();
+        assert_eq!(parse_intent("close slack").unwrap(), Action::QuitApp { name: name("Slack") });
+        assert_eq!(parse_intent("quit spotify").unwrap(), Action::QuitApp { name: name("Spotify") });
+        assert_eq!(parse_intent("kill zoom").unwrap(), Action::ForceQuitApp { name: name("Zoom") });
+        assert_eq!(parse_intent("force quit the zoom app").unwrap(), Action::ForceQuitApp { name: name("Zoom") });
+        assert_eq!(parse_intent("hide mail").unwrap(), Action::HideApp { name: name("Mail") });
+        assert_eq!(
+            parse_intent("switch to google chrome").unwrap(),
+            Action::FocusApp { name: name("Google Chrome") }
+        );
+    }
+
+    #[test]
+    fn test_parse_app_control_uses_aliases() {
+        let mut options = ParseOptions::default();
+        options.aliases.add("code", "Visual Studio Code");
+        assert_eq!(
+            parse_intent_with("quit code", &options).unwrap().action,
+            Action::QuitApp { name: "Visual Studio Code".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_parse_media() {
+        assert_eq!(parse_intent("pause").unwrap(), Action::Pause);
+        assert_eq!(parse_intent("pause the music").unwrap(), Action::Pause);
+        assert_eq!(parse_intent("play").unwrap(), Action::Play);
+        assert_eq!(parse_intent("resume playback").unwrap(), Action::Play);
+        assert_eq!(parse_intent("next track").unwrap(), Action::NextTrack);
+        assert_eq!(parse_intent("skip this song").unwrap(), Action::NextTrack);
+        assert_eq!(parse_intent("previous song").unwrap(), Action::PreviousTrack);
+        assert_eq!(parse_intent("toggle playback").unwrap(), Action::TogglePlayback);
+        assert_eq!(parse_intent("what's playing?").unwrap(), Action::NowPlaying);
+        assert_eq!(parse_intent("What song is this").unwrap(), Action::NowPlaying);
+        assert!(parse_intent("play despacito").is_err());
+    }
+
+    #[test]
+    fn test_parse_power() {
+        for (input, action) in [
+            ("lock the screen", Action::LockScreen),
+            ("lock my computer", Action::LockScreen),
+            ("go to sleep", Action::Sleep),
+            ("put the computer to sleep", Action::Sleep),
+            ("restart", Action::Restart),
+            ("reboot the system now", Action::Restart),
+            ("shut down", Action::Shutdown),
+            ("turn off the computer", Action::Shutdown),
+            ("log out", Action::LogOut),
+            ("sign out", Action::LogOut),
+        ] {
+            assert_eq!(parse_intent(input).unwrap(), action, "{}", input);
+        }
+        assert!(parse_intent("restart spotify").is_err());
+    }
+
+    #[test]
+    fn test_parse_clipboard() {
+        let mut options = ParseOptions::default();
+        options.snippets.text.insert("email".to_string(), "me@example.com".to_string());
+        let parse = |input: &str| parse_intent_with(input, &options).map(|i| i.action);
+
+        assert_eq!(
+            parse("copy my email to the clipboard").unwrap(),
+            Action::CopyText { text: "me@example.com".to_string() }
+        );
+        assert_eq!(parse("copy hello world").unwrap(), Action::CopyText { text: "hello world".to_string() });
+        assert!(matches!(parse("copy my phone number"), Err(ParseError::UnknownSnippet(name)) if name == "phone number"));
+        assert!(matches!(parse("copy to the clipboard"), Err(ParseError::MissingCopyText)));
+
+        assert_eq!(parse("what's on my clipboard?").unwrap(), Action::ReadClipboard);
+        assert_eq!(parse("read the clipboard").unwrap(), Action::ReadClipboard);
+
+        for (input, transform) in [
+            ("make the clipboard lowercase", TextTransform::Lowercase),
+            ("convert my clipboard to upper case", TextTransform::Uppercase),
+            ("title case the clipboard", TextTransform::TitleCase),
+            ("trim the clipboard", TextTransform::Trim),
+            ("strip formatting from the clipboard", TextTransform::StripFormatting),
+            ("url decode the clipboard", TextTransform::UrlDecode),
+        ] {
+            assert_eq!(parse(input).unwrap(), Action::TransformClipboard { transform }, "{}", input);
+        }
+        assert!(parse("make the clipboard purple").is_err());
+    }
+
+    #[test]
+    fn test_parse_notify() {
+        let notify = |body: &str| Action::Notify { title: NOTIFICATION_TITLE.to_string(), body: body.to_string() };
+        assert_eq!(parse_intent("notify me 'stand up'").unwrap(), notify("stand up"));
+        assert_eq!(parse_intent("notify me to drink water").unwrap(), notify("drink water"));
+        assert_eq!(
+            parse_intent("send a notification saying \"build done\"").unwrap(),
+            notify("build done")
+        );
+        assert!(matches!(parse_intent("notify me"), Err(ParseError::MissingNotificationText)));
+    }
+
+    #[test]
+    fn test_parse_web_search() {
+        let search = |query: &str, engine: &str| Action::WebSearch {
+            query: query.to_string(),
+            engine: engine.to_string(),
+        };
+
+        assert_eq!(parse_intent("search for rust lifetimes").unwrap(), search("rust lifetimes", "duckduckgo"));
+        assert_eq!(parse_intent("look up the weather").unwrap(), search("the weather", "duckduckgo"));
+        assert_eq!(parse_intent("google best pizza near me").unwrap(), search("best pizza near me", "google"));
+        assert_eq!(parse_intent("search github for clap derive").unwrap(), search("clap derive", "github"));
+        assert_eq!(parse_intent("search for lofi beats on youtube").unwrap(), search("lofi beats", "youtube"));
+        assert_eq!(parse_intent("search duck duck go for privacy").unwrap(), search("privacy", "duckduckgo"));
+        // An unknown "engine" is just part of the query
+        assert_eq!(parse_intent("search for things to do on mars").unwrap(), search("things to do on mars", "duckduckgo"));
+    }
+
+    #[test]
+    fn test_parse_web_search_missing_query() {
+        assert!(matches!(parse_intent("search"), Err(ParseError::MissingSearchQuery)));
+        assert!(matches!(parse_intent("search for"), Err(ParseError::MissingSearchQuery)));
+    }
+
     #[test]
     fn test_parse_set_volume() {
         let action = parse_intent("set volume to 40").unwrap();
@@ -212,6 +1234,122 @@ mod tests {
         assert_eq!(action, Action::SetVolume { level: 50 });
     }
 
+    #[test]
+    fn test_parse_volume_spoken_numbers() {
+        let cases = [
+            ("set volume to forty two", 42),
+            ("volume 40%", 40),
+            ("set volume to 40 percent", 40),
+            ("volume half", 50),
+            ("set volume to max", 100),
+            ("volume zero", 0),
+        ];
+        for (input, level) in cases {
+            assert_eq!(
+                parse_intent(input).unwrap(),
+                Action::SetVolume { level },
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_volume_spoken_out_of_range() {
+        assert!(matches!(
+            parse_intent("set volume to two hundred"),
+            Err(ParseError::InvalidVolume(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_volume_down_by_spoken_amount() {
+        assert_eq!(
+            parse_intent("turn the volume down by fifteen percent").unwrap(),
+            Action::AdjustVolume { delta: -15 }
+        );
+    }
+
+    #[test]
+    fn test_parse_volume_up_default_step() {
+        for input in ["turn it up", "volume up", "louder", "turn the volume up"] {
+            assert_eq!(
+                parse_intent(input).unwrap(),
+                Action::AdjustVolume { delta: DEFAULT_VOLUME_STEP as i8 },
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_volume_down_by_amount() {
+        assert_eq!(
+            parse_intent("volume down by 10").unwrap(),
+            Action::AdjustVolume { delta: -10 }
+        );
+        assert_eq!(
+            parse_intent("turn it down 25%").unwrap(),
+            Action::AdjustVolume { delta: -25 }
+        );
+    }
+
+    #[test]
+    fn test_parse_volume_small_step() {
+        assert_eq!(
+            parse_intent("a bit quieter").unwrap(),
+            Action::AdjustVolume { delta: -(SMALL_VOLUME_STEP as i8) }
+        );
+        assert_eq!(
+            parse_intent("make it a little louder").unwrap(),
+            Action::AdjustVolume { delta: SMALL_VOLUME_STEP as i8 }
+        );
+    }
+
+    #[test]
+    fn test_parse_volume_adjust_out_of_range() {
+        assert!(parse_intent("volume up by 200").is_err());
+    }
+
+    #[test]
+    fn test_parse_volume_adjust_ignores_other_commands() {
+        assert_eq!(
+            parse_intent("open lower").unwrap(),
+            Action::OpenApp { name: "Lower".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_parse_set_brightness() {
+        for (input, level) in [
+            ("set brightness to 70", 70),
+            ("brightness 40%", 40),
+            ("set the screen brightness to half", 50),
+            ("brightness max", 100),
+        ] {
+            assert_eq!(parse_intent(input).unwrap(), Action::SetBrightness { level }, "{}", input);
+        }
+        assert!(matches!(parse_intent("brightness 150"), Err(ParseError::InvalidBrightness(_))));
+        assert!(matches!(parse_intent("set brightness to"), Err(ParseError::MissingBrightnessLevel)));
+    }
+
+    #[test]
+    fn test_parse_brightness_adjust() {
+        let step = DEFAULT_BRIGHTNESS_STEP as i8;
+        assert_eq!(parse_intent("brighter").unwrap(), Action::AdjustBrightness { delta: step });
+        assert_eq!(parse_intent("dim the screen").unwrap(), Action::AdjustBrightness { delta: -step });
+        assert_eq!(
+            parse_intent("turn the brightness down by twenty").unwrap(),
+            Action::AdjustBrightness { delta: -20 }
+        );
+        assert_eq!(
+            parse_intent("make the display a bit brighter").unwrap(),
+            Action::AdjustBrightness { delta: SMALL_BRIGHTNESS_STEP as i8 }
+        );
+        // Without a brightness word, "up"/"down" stay volume commands
+        assert_eq!(parse_intent("turn it up").unwrap(), Action::AdjustVolume { delta: DEFAULT_VOLUME_STEP as i8 });
+    }
+
     #[test]
     fn test_parse_mute() {
         let action = parse_intent("mute").unwrap();
@@ -254,8 +1392,178 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_misheard_verb() {
+        let interpretation = parse_intent_with("opne safari", &ParseOptions::default()).unwrap();
+        assert_eq!(
+            interpretation.action,
+            Action::OpenApp { name: "Safari".to_string() }
+        );
+        assert_eq!(interpretation.corrections.len(), 1);
+        assert_eq!(interpretation.corrections[0].kind, CorrectionKind::Verb);
+        assert_eq!(interpretation.corrections[0].from, "opne");
+        assert_eq!(interpretation.corrections[0].to, "open");
+    }
+
+    #[test]
+    fn test_parse_split_app_name() {
+        for (input, app) in [("open sa fari", "Safari"), ("open fire fox", "Firefox")] {
+            let interpretation = parse_intent_with(input, &ParseOptions::default()).unwrap();
+            assert_eq!(interpretation.action, Action::OpenApp { name: app.to_string() });
+            assert_eq!(interpretation.corrections[0].kind, CorrectionKind::AppName);
+            assert_eq!(interpretation.corrections[0].method, MatchMethod::Joined);
+        }
+    }
+
+    #[test]
+    fn test_parse_exact_app_has_no_corrections() {
+        let interpretation = parse_intent_with("open safari", &ParseOptions::default()).unwrap();
+        assert!(interpretation.corrections.is_empty());
+    }
+
+    #[test]
+    fn test_parse_fuzzy_disabled() {
+        let options = ParseOptions {
+            fuzzy: FuzzyConfig::disabled(),
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            parse_intent_with("opne safari", &options),
+            Err(ParseError::UnrecognizedCommand(_))
+        ));
+        assert_eq!(
+            parse_intent_with("open fire fox", &options).unwrap().action,
+            Action::OpenApp { name: "Fire Fox".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_ranked_exact_match() {
+        let candidates = parse_intent_ranked("set volume to 40", &ParseOptions::default(), 3).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].action, Action::SetVolume { level: 40 });
+        assert_eq!(candidates[0].confidence, 1.0);
+        assert_eq!(candidates[0].rule, "set_volume");
+        assert!(!candidates[0].needs_confirmation());
+    }
+
+    #[test]
+    fn test_ranked_multiple_candidates() {
+        let candidates = parse_intent_ranked("open nots", &ParseOptions::default(), 3).unwrap();
+        assert!(candidates.len() > 1);
+        assert_eq!(candidates[0].action, Action::OpenApp { name: "Notes".to_string() });
+        assert_eq!(candidates[0].rule, "open");
+        assert!(candidates.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+        assert!(candidates
+            .iter()
+            .any(|c| c.action == Action::OpenApp { name: "Nots".to_string() }));
+    }
+
+    #[test]
+    fn test_ranked_limit() {
+        let candidates = parse_intent_ranked("open nots", &ParseOptions::default(), 1).unwrap();
+        assert_eq!(candidates.len(), 1);
+    }
+
+    #[test]
+    fn test_ranked_unknown_app_is_low_confidence() {
+        let candidates = parse_intent_ranked("open photoshop", &ParseOptions::default(), 3).unwrap();
+        assert_eq!(candidates[0].confidence, UNKNOWN_APP_CONFIDENCE);
+        assert!(candidates[0].needs_confirmation());
+    }
+
+    #[test]
+    fn test_catalog_resolution() {
+        let app = |name: &str, id: &str| AppEntry {
+            name: name.to_string(),
+            id: id.to_string(),
+            path: Default::default(),
+            exec: None,
+        };
+        let options = ParseOptions {
+            catalog: AppCatalog {
+                dirs: Vec::new(),
+                apps: vec![
+                    app("Visual Studio Code", "code"),
+                    app("Google Chrome", "google-chrome"),
+                    app("Chrome Remote Desktop", "chrome-remote-desktop"),
+                ],
+            },
+            ..ParseOptions::default()
+        };
+
+        let candidates = parse_intent_ranked("open code", &options, 3).unwrap();
+        assert_eq!(candidates[0].action, Action::OpenApp { name: "Visual Studio Code".to_string() });
+        assert_eq!(candidates[0].confidence, 1.0);
+
+        let candidates = parse_intent_ranked("open chrome", &options, 3).unwrap();
+        let names: Vec<_> = candidates.iter().map(|c| c.action.to_string()).collect();
+        assert_eq!(
+            names,
+            vec!["OpenApp(name=\"Google Chrome\")", "OpenApp(name=\"Chrome Remote Desktop\")"]
+        );
+        assert!(candidates[0].needs_confirmation());
+    }
+
+    #[test]
+    fn test_ranked_corrected_verb_confidence() {
+        let candidates = parse_intent_ranked("opne safari", &ParseOptions::default(), 3).unwrap();
+        assert_eq!(candidates[0].action, Action::OpenApp { name: "Safari".to_string() });
+        assert_eq!(candidates[0].confidence, 0.75);
+    }
+
+    #[test]
+    fn test_ranked_error() {
+        assert!(matches!(
+            parse_intent_ranked("set volume to abc", &ParseOptions::default(), 3),
+            Err(ParseError::InvalidVolume(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        let mut options = ParseOptions::default();
+        options.aliases.add("code", "Visual Studio Code");
+        options.aliases.add("iterm", "iTerm");
+        options.aliases.add("gh", "https://github.com");
+
+        let interpretation = parse_intent_with("open code", &options).unwrap();
+        assert_eq!(
+            interpretation.action,
+            Action::OpenApp { name: "Visual Studio Code".to_string() }
+        );
+        assert_eq!(interpretation.confidence, 1.0);
+        assert!(interpretation.corrections.is_empty());
+
+        assert_eq!(
+            parse_intent_with("open iterm", &options).unwrap().action,
+            Action::OpenApp { name: "iTerm".to_string() }
+        );
+        assert_eq!(
+            parse_intent_with("open gh", &options).unwrap().action,
+            Action::OpenUrl { url: "https://github.com".to_string() }
+        );
+    }
+
     #[test]
     fn test_normalize_input() {
         assert_eq!(normalize_input("  HELLO   WORLD  "), "hello world");
     }
+
+    #[test]
+    fn test_action_examples_cover_every_action_in_order() {
+        let schema = serde_json::to_value(<Action as utoipa::PartialSchema>::schema()).unwrap();
+        let variants: Vec<&str> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["type"]["enum"][0].as_str().unwrap())
+            .collect();
+
+        let kinds: Vec<&str> = ACTION_EXAMPLES
+            .iter()
+            .map(|example| parse_intent(example).unwrap_or_else(|e| panic!("{}: {}", example, e)).kind())
+            .collect();
+        assert_eq!(kinds, variants);
+    }
 }
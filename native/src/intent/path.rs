@@ -0,0 +1,203 @@
+//! File and folder targets for Luna.
+//!
+//! Resolves what follows "open" when it names a place on disk: explicit
+//! paths ("~/notes/todo.md", "$HOME/src") and well-known folders
+//! ("downloads", "my documents folder").
+
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Well-known folders: spoken names, XDG user-dirs key, default directory
+/// under the home directory.
+const WELL_KNOWN_FOLDERS: &[(&[&str], &str, &str)] = &[
+    (&["downloads", "download"], "XDG_DOWNLOAD_DIR", "Downloads"),
+    (&["documents", "docs"], "XDG_DOCUMENTS_DIR", "Documents"),
+    (&["desktop"], "XDG_DESKTOP_DIR", "Desktop"),
+    (&["music"], "XDG_MUSIC_DIR", "Music"),
+    (&["pictures", "photos"], "XDG_PICTURES_DIR", "Pictures"),
+    (&["videos", "movies"], "XDG_VIDEOS_DIR", if cfg!(target_os = "macos") { "Movies" } else { "Videos" }),
+    (&["public"], "XDG_PUBLICSHARE_DIR", "Public"),
+    (&["templates"], "XDG_TEMPLATES_DIR", "Templates"),
+];
+
+/// Whether the target is written as a path rather than a name.
+pub fn looks_like_path(target: &str) -> bool {
+    target.starts_with(['/', '~', '$']) || target.starts_with("./") || target.starts_with("../")
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references using the process
+/// environment.
+pub fn expand_path(target: &str) -> PathBuf {
+    expand_path_with(target, |name| env::var(name).ok())
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references, looking variables
+/// up with `var`.
+///
+/// Spoken input is lowercased, so a variable that isn't set is retried in
+/// uppercase ("$home" -> `$HOME`). Unknown variables are left as written.
+pub fn expand_path_with(target: &str, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let lookup = |name: &str| var(name).or_else(|| var(&name.to_uppercase()));
+
+    let mut expanded = String::with_capacity(target.len());
+    let mut rest = target;
+    if rest == "~" || rest.starts_with("~/") {
+        match lookup("HOME") {
+            Some(home) => expanded.push_str(&home),
+            None => expanded.push('~'),
+        }
+        rest = &rest[1..];
+    }
+
+    while let Some(at) = rest.find('$') {
+        expanded.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let (name, len) = match after.strip_prefix('{').and_then(|s| s.split_once('}')) {
+            Some((name, _)) => (name, name.len() + 2),
+            None => {
+                let len = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..len], len)
+            }
+        };
+
+        match lookup(name).filter(|_| !name.is_empty()) {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[at..at + 1 + len]),
+        }
+        rest = &after[len..];
+    }
+    expanded.push_str(rest);
+
+    PathBuf::from(expanded)
+}
+
+/// Find an existing path, matching components case-insensitively when there
+/// is no exact match ("~/notes" finds "~/Notes"). Speech-to-text input has
+/// no reliable case.
+pub fn resolve_existing(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        let Component::Normal(name) = component else {
+            resolved.push(component);
+            continue;
+        };
+
+        let exact = resolved.join(name);
+        if exact.exists() {
+            resolved = exact;
+            continue;
+        }
+
+        let wanted = name.to_string_lossy().to_lowercase();
+        let dir = if resolved.as_os_str().is_empty() { Path::new(".") } else { &resolved };
+        let found = fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .find(|entry| entry.file_name().to_string_lossy().to_lowercase() == wanted)?;
+        resolved.push(found.file_name());
+    }
+    Some(resolved)
+}
+
+/// The directory for a well-known folder name ("downloads", "home").
+pub fn well_known_folder(name: &str) -> Option<PathBuf> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map_or_else(|| home.join(".config"), PathBuf::from);
+    let user_dirs = fs::read_to_string(config_home.join("user-dirs.dirs")).ok();
+
+    well_known_folder_in(name, &home, user_dirs.as_deref())
+}
+
+/// The directory for a well-known folder name, given the home directory and
+/// the contents of the XDG `user-dirs.dirs` file, if any.
+pub fn well_known_folder_in(name: &str, home: &Path, user_dirs: Option<&str>) -> Option<PathBuf> {
+    if name == "home" {
+        return Some(home.to_path_buf());
+    }
+
+    let (_, key, default) = WELL_KNOWN_FOLDERS
+        .iter()
+        .find(|(names, _, _)| names.contains(&name))?;
+
+    // XDG_DOWNLOAD_DIR="$HOME/Downloads"
+    let configured = user_dirs.and_then(|contents| {
+        contents.lines().find_map(|line| {
+            let value = line.trim().strip_prefix(key)?.trim_start().strip_prefix('=')?;
+            let value = value.trim().trim_matches('"');
+            let relative = value.strip_prefix("$HOME").unwrap_or(value).trim_start_matches('/');
+            Some(home.join(relative))
+        })
+    });
+
+    Some(configured.unwrap_or_else(|| home.join(default)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/luna".to_string()),
+            "PROJECTS" => Some("/srv/projects".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_looks_like_path() {
+        assert!(looks_like_path("~/notes"));
+        assert!(looks_like_path("/etc/hosts"));
+        assert!(looks_like_path("$home/src"));
+        assert!(looks_like_path("./build"));
+        assert!(!looks_like_path("safari"));
+        assert!(!looks_like_path("github.com"));
+    }
+
+    #[test]
+    fn test_expand_path() {
+        assert_eq!(expand_path_with("~/notes/todo.md", vars), PathBuf::from("/home/luna/notes/todo.md"));
+        assert_eq!(expand_path_with("~", vars), PathBuf::from("/home/luna"));
+        assert_eq!(expand_path_with("$home/src", vars), PathBuf::from("/home/luna/src"));
+        assert_eq!(expand_path_with("${PROJECTS}/luna", vars), PathBuf::from("/srv/projects/luna"));
+        assert_eq!(expand_path_with("$NOPE/x", vars), PathBuf::from("$NOPE/x"));
+        assert_eq!(expand_path_with("~other/x", vars), PathBuf::from("~other/x"));
+    }
+
+    #[test]
+    fn test_well_known_folder() {
+        let home = Path::new("/home/luna");
+        assert_eq!(well_known_folder_in("home", home, None), Some(home.to_path_buf()));
+        assert_eq!(well_known_folder_in("downloads", home, None), Some(home.join("Downloads")));
+        assert_eq!(well_known_folder_in("photos", home, None), Some(home.join("Pictures")));
+        assert_eq!(well_known_folder_in("safari", home, None), None);
+
+        let user_dirs = "# written by xdg-user-dirs-update\nXDG_DOWNLOAD_DIR=\"$HOME/Téléchargements\"\n";
+        assert_eq!(
+            well_known_folder_in("downloads", home, Some(user_dirs)),
+            Some(home.join("Téléchargements"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_existing_ignores_case() {
+        let dir = env::temp_dir().join(format!("luna-path-{}", std::process::id()));
+        fs::create_dir_all(dir.join("Notes")).unwrap();
+        fs::write(dir.join("Notes").join("TODO.md"), "").unwrap();
+
+        let lowered = PathBuf::from(dir.to_string_lossy().to_string()).join("notes").join("todo.md");
+        assert_eq!(resolve_existing(&lowered), Some(dir.join("Notes").join("TODO.md")));
+        assert_eq!(resolve_existing(&dir.join("missing")), None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,35 +4,260 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use utoipa::ToSchema;
 
 /// Represents a parsed user action/intent.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type", content = "params")]
 pub enum Action {
     /// Open an application by name
     OpenApp { name: String },
 
+    /// Quit a running application, letting it save its work
+    QuitApp { name: String },
+
+    /// Kill a running application immediately (needs confirmation)
+    ForceQuitApp { name: String },
+
+    /// Hide (minimize) a running application's windows
+    HideApp { name: String },
+
+    /// Bring a running application to the front
+    FocusApp { name: String },
+
     /// Open a URL in the default browser
     OpenUrl { url: String },
 
+    /// Open a file or folder with its default app, or reveal it in the file manager
+    OpenPath { path: String, reveal: bool },
+
+    /// Search the web for `query` with a named search engine
+    WebSearch { query: String, engine: String },
+
     /// Set the system volume to a specific level (0-100)
-    SetVolume { level: u8 },
+    SetVolume {
+        #[schema(maximum = 100)]
+        level: u8,
+    },
+
+    /// Change the system volume relative to its current level (clamped to 0-100)
+    AdjustVolume {
+        #[schema(minimum = -100, maximum = 100)]
+        delta: i8,
+    },
 
     /// Mute system audio
     Mute,
 
     /// Unmute system audio
     Unmute,
+
+    /// Set the screen brightness to a specific level (0-100)
+    SetBrightness {
+        #[schema(maximum = 100)]
+        level: u8,
+    },
+
+    /// Change the screen brightness relative to its current level (clamped to 0-100)
+    AdjustBrightness {
+        #[schema(minimum = -100, maximum = 100)]
+        delta: i8,
+    },
+
+    /// Resume media playback
+    Play,
+
+    /// Pause media playback
+    Pause,
+
+    /// Play if paused, pause if playing
+    TogglePlayback,
+
+    /// Skip to the next track
+    NextTrack,
+
+    /// Go back to the previous track
+    PreviousTrack,
+
+    /// Report the track that is playing
+    NowPlaying,
+
+    /// Lock the screen (needs confirmation)
+    LockScreen,
+
+    /// Put the computer to sleep (needs confirmation)
+    Sleep,
+
+    /// Restart the computer (needs confirmation)
+    Restart,
+
+    /// Shut the computer down (needs confirmation)
+    Shutdown,
+
+    /// End the user's session (needs confirmation)
+    LogOut,
+
+    /// Put text on the clipboard
+    CopyText { text: String },
+
+    /// Report the text on the clipboard
+    ReadClipboard,
+
+    /// Rewrite the text on the clipboard
+    TransformClipboard {
+        #[schema(inline)]
+        transform: TextTransform,
+    },
+
+    /// Post a desktop notification
+    Notify { title: String, body: String },
+}
+
+/// A rewrite applied to clipboard text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TextTransform {
+    Lowercase,
+    Uppercase,
+    /// Capitalize the first letter of every word
+    TitleCase,
+    /// Remove leading and trailing whitespace
+    Trim,
+    /// Keep plain text only, dropping rich text and invisible characters
+    StripFormatting,
+    /// Decode `%XX` escapes
+    UrlDecode,
+}
+
+impl fmt::Display for TextTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TextTransform::Lowercase => "lowercase",
+            TextTransform::Uppercase => "uppercase",
+            TextTransform::TitleCase => "title case",
+            TextTransform::Trim => "trim",
+            TextTransform::StripFormatting => "strip formatting",
+            TextTransform::UrlDecode => "url decode",
+        })
+    }
+}
+
+impl Action {
+    /// The variant's name, as in the serialized `type` tag ("OpenApp").
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Action::OpenApp { .. } => "OpenApp",
+            Action::QuitApp { .. } => "QuitApp",
+            Action::ForceQuitApp { .. } => "ForceQuitApp",
+            Action::HideApp { .. } => "HideApp",
+            Action::FocusApp { .. } => "FocusApp",
+            Action::OpenUrl { .. } => "OpenUrl",
+            Action::OpenPath { .. } => "OpenPath",
+            Action::WebSearch { .. } => "WebSearch",
+            Action::SetVolume { .. } => "SetVolume",
+            Action::AdjustVolume { .. } => "AdjustVolume",
+            Action::Mute => "Mute",
+            Action::Unmute => "Unmute",
+            Action::SetBrightness { .. } => "SetBrightness",
+            Action::AdjustBrightness { .. } => "AdjustBrightness",
+            Action::Play => "Play",
+            Action::Pause => "Pause",
+            Action::TogglePlayback => "TogglePlayback",
+            Action::NextTrack => "NextTrack",
+            Action::PreviousTrack => "PreviousTrack",
+            Action::NowPlaying => "NowPlaying",
+            Action::LockScreen => "LockScreen",
+            Action::Sleep => "Sleep",
+            Action::Restart => "Restart",
+            Action::Shutdown => "Shutdown",
+            Action::LogOut => "LogOut",
+            Action::CopyText { .. } => "CopyText",
+            Action::ReadClipboard => "ReadClipboard",
+            Action::TransformClipboard { .. } => "TransformClipboard",
+            Action::Notify { .. } => "Notify",
+        }
+    }
+
+    /// The app this action targets, if any.
+    pub fn app_name(&self) -> Option<&str> {
+        match self {
+            Action::OpenApp { name }
+            | Action::QuitApp { name }
+            | Action::ForceQuitApp { name }
+            | Action::HideApp { name }
+            | Action::FocusApp { name } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// This action aimed at a different app; actions without an app are
+    /// returned unchanged.
+    pub fn with_app_name(self, name: String) -> Self {
+        match self {
+            Action::OpenApp { .. } => Action::OpenApp { name },
+            Action::QuitApp { .. } => Action::QuitApp { name },
+            Action::ForceQuitApp { .. } => Action::ForceQuitApp { name },
+            Action::HideApp { .. } => Action::HideApp { name },
+            Action::FocusApp { .. } => Action::FocusApp { name },
+            action => action,
+        }
+    }
+
+    /// The question to ask before running an action that is hard to undo,
+    /// or `None` if it can run without confirmation.
+    pub fn confirmation_prompt(&self) -> Option<String> {
+        match self {
+            Action::ForceQuitApp { name } => {
+                Some(format!("Force quit {}? Unsaved work will be lost.", name))
+            }
+            Action::LockScreen => Some("Lock the screen?".to_string()),
+            Action::Sleep => Some("Put the computer to sleep?".to_string()),
+            Action::Restart => Some("Restart the computer? Unsaved work in open apps may be lost.".to_string()),
+            Action::Shutdown => Some("Shut down the computer? Unsaved work in open apps may be lost.".to_string()),
+            Action::LogOut => Some("Log out? Open apps will be closed.".to_string()),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Action::OpenApp { name } => write!(f, "OpenApp(name=\"{}\")", name),
+            Action::QuitApp { name } => write!(f, "QuitApp(name=\"{}\")", name),
+            Action::ForceQuitApp { name } => write!(f, "ForceQuitApp(name=\"{}\")", name),
+            Action::HideApp { name } => write!(f, "HideApp(name=\"{}\")", name),
+            Action::FocusApp { name } => write!(f, "FocusApp(name=\"{}\")", name),
             Action::OpenUrl { url } => write!(f, "OpenUrl(url=\"{}\")", url),
+            Action::OpenPath { path, reveal } => {
+                write!(f, "OpenPath(path=\"{}\", reveal={})", path, reveal)
+            }
+            Action::WebSearch { query, engine } => {
+                write!(f, "WebSearch(query=\"{}\", engine=\"{}\")", query, engine)
+            }
             Action::SetVolume { level } => write!(f, "SetVolume(level={})", level),
+            Action::AdjustVolume { delta } => write!(f, "AdjustVolume(delta={:+})", delta),
             Action::Mute => write!(f, "Mute"),
             Action::Unmute => write!(f, "Unmute"),
+            Action::SetBrightness { level } => write!(f, "SetBrightness(level={})", level),
+            Action::AdjustBrightness { delta } => write!(f, "AdjustBrightness(delta={:+})", delta),
+            Action::Play => write!(f, "Play"),
+            Action::Pause => write!(f, "Pause"),
+            Action::TogglePlayback => write!(f, "TogglePlayback"),
+            Action::NextTrack => write!(f, "NextTrack"),
+            Action::PreviousTrack => write!(f, "PreviousTrack"),
+            Action::NowPlaying => write!(f, "NowPlaying"),
+            Action::LockScreen => write!(f, "LockScreen"),
+            Action::Sleep => write!(f, "Sleep"),
+            Action::Restart => write!(f, "Restart"),
+            Action::Shutdown => write!(f, "Shutdown"),
+            Action::LogOut => write!(f, "LogOut"),
+            Action::CopyText { text } => write!(f, "CopyText(text=\"{}\")", text),
+            Action::ReadClipboard => write!(f, "ReadClipboard"),
+            Action::TransformClipboard { transform } => {
+                write!(f, "TransformClipboard(transform={})", transform)
+            }
+            Action::Notify { title, body } => write!(f, "Notify(title=\"{}\", body=\"{}\")", title, body),
         }
     }
 }
@@ -51,12 +276,78 @@ mod tests {
             Action::OpenUrl { url: "https://google.com".to_string() }.to_string(),
             "OpenUrl(url=\"https://google.com\")"
         );
+        assert_eq!(
+            Action::OpenPath { path: "/tmp".to_string(), reveal: true }.to_string(),
+            "OpenPath(path=\"/tmp\", reveal=true)"
+        );
+        assert_eq!(
+            Action::WebSearch { query: "rust".to_string(), engine: "google".to_string() }.to_string(),
+            "WebSearch(query=\"rust\", engine=\"google\")"
+        );
         assert_eq!(
             Action::SetVolume { level: 40 }.to_string(),
             "SetVolume(level=40)"
         );
+        assert_eq!(
+            Action::AdjustVolume { delta: -10 }.to_string(),
+            "AdjustVolume(delta=-10)"
+        );
+        assert_eq!(
+            Action::AdjustVolume { delta: 5 }.to_string(),
+            "AdjustVolume(delta=+5)"
+        );
+        assert_eq!(
+            Action::AdjustBrightness { delta: -20 }.to_string(),
+            "AdjustBrightness(delta=-20)"
+        );
+        assert_eq!(
+            Action::ForceQuitApp { name: "Zoom".to_string() }.to_string(),
+            "ForceQuitApp(name=\"Zoom\")"
+        );
         assert_eq!(Action::Mute.to_string(), "Mute");
         assert_eq!(Action::Unmute.to_string(), "Unmute");
+        assert_eq!(Action::NextTrack.to_string(), "NextTrack");
+    }
+
+    #[test]
+    fn test_app_name() {
+        let quit = Action::QuitApp { name: "Slack".to_string() };
+        assert_eq!(quit.app_name(), Some("Slack"));
+        assert_eq!(
+            quit.with_app_name("Zoom".to_string()),
+            Action::QuitApp { name: "Zoom".to_string() }
+        );
+        assert_eq!(Action::Mute.app_name(), None);
+        assert_eq!(Action::Mute.with_app_name("Zoom".to_string()), Action::Mute);
+    }
+
+    #[test]
+    fn test_clipboard_display_and_serialization() {
+        let action = Action::TransformClipboard { transform: TextTransform::TitleCase };
+        assert_eq!(action.to_string(), "TransformClipboard(transform=title case)");
+        assert_eq!(
+            serde_json::to_string(&action).unwrap(),
+            r#"{"type":"TransformClipboard","params":{"transform":"title_case"}}"#
+        );
+        assert_eq!(
+            Action::Notify { title: "Luna".to_string(), body: "stand up".to_string() }.to_string(),
+            "Notify(title=\"Luna\", body=\"stand up\")"
+        );
+        assert_eq!(
+            Action::CopyText { text: "me@example.com".to_string() }.to_string(),
+            "CopyText(text=\"me@example.com\")"
+        );
+    }
+
+    #[test]
+    fn test_confirmation_prompt() {
+        assert!(Action::ForceQuitApp { name: "Zoom".to_string() }
+            .confirmation_prompt()
+            .is_some_and(|prompt| prompt.contains("Zoom")));
+        assert_eq!(Action::QuitApp { name: "Zoom".to_string() }.confirmation_prompt(), None);
+        for action in [Action::LockScreen, Action::Sleep, Action::Restart, Action::Shutdown, Action::LogOut] {
+            assert!(action.confirmation_prompt().is_some(), "{}", action);
+        }
     }
 
     #[test]
@@ -66,4 +357,16 @@ mod tests {
         assert!(json.contains("SetVolume"));
         assert!(json.contains("50"));
     }
+
+    #[test]
+    fn test_kind_matches_type_tag() {
+        for action in [
+            Action::Mute,
+            Action::SetVolume { level: 50 },
+            Action::TransformClipboard { transform: TextTransform::Trim },
+        ] {
+            let json = serde_json::to_value(&action).unwrap();
+            assert_eq!(json["type"], action.kind());
+        }
+    }
 }
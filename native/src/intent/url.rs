@@ -0,0 +1,195 @@
+//! URL detection for Luna.
+//!
+//! Decides whether the target of "open ..." is a web address or an app.
+//! "open github.com" should open a browser, but "open node.js" and "open
+//! notes.app" should not, even though they contain dots.
+
+use std::net::IpAddr;
+
+/// Top-level domains recognized on bare domains. Multi-level public
+/// suffixes ("co.uk", "com.au") end in one of these.
+const KNOWN_TLDS: &[&str] = &[
+    // Generic
+    "com", "org", "net", "edu", "gov", "mil", "int", "info", "biz", "io", "ai", "co", "dev", "app",
+    "me", "tv", "xyz", "site", "online", "tech", "store", "blog", "cloud", "page", "news", "zip",
+    "mov",
+    // Country codes
+    "us", "uk", "ca", "au", "nz", "de", "fr", "es", "it", "nl", "be", "ch", "at", "se", "no", "dk",
+    "fi", "pl", "cz", "pt", "ie", "ru", "ua", "jp", "cn", "kr", "in", "br", "mx", "ar", "za", "sg",
+    "hk", "tw", "il", "tr", "gr", "eu", "fm", "gg", "ly", "sh", "md", "rs", "py", "so", "to",
+];
+
+/// TLDs that are also common file extensions or app suffixes, so a bare
+/// "name.tld" could mean either ("notes.app", "deploy.sh", "readme.md").
+const AMBIGUOUS_TLDS: &[&str] = &["app", "md", "sh", "py", "rs", "pl", "zip", "mov"];
+
+/// Schemes that are written without "//".
+const OPAQUE_SCHEMES: &[&str] = &["mailto:", "tel:", "sms:", "facetime:"];
+
+/// What the target of an "open" command refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenTarget {
+    /// A web address, normalized to include a scheme
+    Url(String),
+    /// An app name
+    App,
+    /// Could be either; the URL reading is included
+    Ambiguous(String),
+}
+
+/// Classify the target of an "open" command.
+///
+/// Recognizes explicit schemes, `localhost` and IP addresses (with optional
+/// port and path) and bare domains ending in a known TLD. Bare domains get
+/// `https://`; localhost and IP addresses get `http://`, since they are
+/// usually local servers.
+///
+/// # Examples
+/// ```
+/// use luna::intent::url::{classify_target, OpenTarget};
+///
+/// assert_eq!(classify_target("github.com"), OpenTarget::Url("https://github.com".to_string()));
+/// assert_eq!(classify_target("node.js"), OpenTarget::App);
+/// ```
+pub fn classify_target(target: &str) -> OpenTarget {
+    let target = target.trim();
+    if target.is_empty() || target.contains(char::is_whitespace) {
+        return OpenTarget::App;
+    }
+
+    let lowered = target.to_lowercase();
+    if has_scheme(&lowered) || OPAQUE_SCHEMES.iter().any(|s| lowered.starts_with(s)) {
+        return OpenTarget::Url(target.to_string());
+    }
+
+    // Split "host:port/path?query" into host and the rest
+    let host_end = lowered.find(['/', '?', '#']).unwrap_or(lowered.len());
+    let (authority, rest) = lowered.split_at(host_end);
+    let Some((host, port)) = split_port(authority) else {
+        return OpenTarget::App;
+    };
+
+    if host == "localhost" || is_ip_address(host) {
+        return OpenTarget::Url(format!("http://{}", target));
+    }
+
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() < 2 || !labels.iter().all(|label| is_valid_label(label)) {
+        return OpenTarget::App;
+    }
+
+    let tld = labels[labels.len() - 1];
+    let is_www = labels[0] == "www";
+    if !KNOWN_TLDS.contains(&tld) && !is_www {
+        return OpenTarget::App;
+    }
+
+    let url = format!("https://{}", target);
+    // A path, port or "www." settles it; "notes.app" alone does not
+    if AMBIGUOUS_TLDS.contains(&tld) && rest.is_empty() && port.is_none() && !is_www {
+        OpenTarget::Ambiguous(url)
+    } else {
+        OpenTarget::Url(url)
+    }
+}
+
+/// Whether `target` is unambiguously a URL.
+pub fn is_url(target: &str) -> bool {
+    matches!(classify_target(target), OpenTarget::Url(_))
+}
+
+/// "scheme://..." with a syntactically valid scheme.
+fn has_scheme(target: &str) -> bool {
+    target.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+    })
+}
+
+/// Split "host:port", returning `None` if the port is not a valid number.
+fn split_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    // "[::1]:8080"
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']')?;
+        return match rest.strip_prefix(':') {
+            Some(port) => Some((host, Some(port.parse().ok()?))),
+            None if rest.is_empty() => Some((host, None)),
+            None => None,
+        };
+    }
+
+    match authority.split_once(':') {
+        Some((host, port)) => Some((host, Some(port.parse().ok()?))),
+        None => Some((authority, None)),
+    }
+}
+
+fn is_ip_address(host: &str) -> bool {
+    host.parse::<IpAddr>().is_ok()
+}
+
+/// A DNS label: letters, digits and inner hyphens.
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> OpenTarget {
+        OpenTarget::Url(s.to_string())
+    }
+
+    #[test]
+    fn test_schemes() {
+        assert_eq!(classify_target("https://google.com"), url("https://google.com"));
+        assert_eq!(classify_target("http://example.com"), url("http://example.com"));
+        assert_eq!(classify_target("ftp://files.example.org"), url("ftp://files.example.org"));
+        assert_eq!(classify_target("mailto:me@example.com"), url("mailto:me@example.com"));
+    }
+
+    #[test]
+    fn test_bare_domains() {
+        assert_eq!(classify_target("github.com"), url("https://github.com"));
+        assert_eq!(classify_target("bbc.co.uk"), url("https://bbc.co.uk"));
+        assert_eq!(classify_target("docs.rs/serde"), url("https://docs.rs/serde"));
+        assert_eq!(classify_target("www.example.internal"), url("https://www.example.internal"));
+    }
+
+    #[test]
+    fn test_local_addresses() {
+        assert_eq!(classify_target("localhost:3000"), url("http://localhost:3000"));
+        assert_eq!(classify_target("localhost"), url("http://localhost"));
+        assert_eq!(classify_target("192.168.1.1"), url("http://192.168.1.1"));
+        assert_eq!(classify_target("127.0.0.1:8080/health"), url("http://127.0.0.1:8080/health"));
+        assert_eq!(classify_target("[::1]:8080"), url("http://[::1]:8080"));
+    }
+
+    #[test]
+    fn test_apps() {
+        assert_eq!(classify_target("node.js"), OpenTarget::App);
+        assert_eq!(classify_target("safari"), OpenTarget::App);
+        assert_eq!(classify_target("visual studio code"), OpenTarget::App);
+        assert_eq!(classify_target("1.5"), OpenTarget::App);
+        assert_eq!(classify_target("localhost:abc"), OpenTarget::App);
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        assert_eq!(
+            classify_target("notes.app"),
+            OpenTarget::Ambiguous("https://notes.app".to_string())
+        );
+        assert_eq!(
+            classify_target("deploy.sh"),
+            OpenTarget::Ambiguous("https://deploy.sh".to_string())
+        );
+        assert_eq!(classify_target("excalidraw.app/room"), url("https://excalidraw.app/room"));
+        assert!(!is_url("notes.app"));
+    }
+}
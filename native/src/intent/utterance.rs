@@ -0,0 +1,223 @@
+//! Compound utterance parsing for Luna.
+//!
+//! People chain requests: "open safari and set volume to 30", "mute, then
+//! open spotify". This module splits such input into ordered steps and
+//! parses each one with the regular intent rules.
+
+use super::parse::{parse_intent_with, Interpretation, ParseError, ParseOptions};
+use super::types::Action;
+
+/// Words and punctuation that separate steps, longest first.
+const SEPARATORS: &[&str] = &[" and then ", " after that ", " then ", " and ", ", ", "; "];
+
+/// One step of a compound utterance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtteranceStep {
+    /// The text of this step ("set volume to 30")
+    pub text: String,
+    /// The parsed action for this step
+    pub interpretation: Interpretation,
+}
+
+/// Parse an utterance that may contain several commands.
+///
+/// Splits on "and", "then", "and then", "after that", commas and
+/// semicolons. A piece that doesn't parse on its own borrows the verb of the
+/// previous step ("open safari and slack"), and failing that is joined back
+/// onto the previous step ("set volume to one hundred and five"), so
+/// separators inside a single command don't break it apart.
+///
+/// # Returns
+/// * `Ok(steps)` - At least one step, in the order spoken
+/// * `Err(ParseError)` - Some piece could not be parsed
+pub fn parse_utterance(input: &str, options: &ParseOptions) -> Result<Vec<UtteranceStep>, ParseError> {
+    let pieces = split_pieces(input);
+    if pieces.len() <= 1 {
+        let interpretation = parse_intent_with(input, options)?;
+        return Ok(vec![UtteranceStep {
+            text: input.trim().to_string(),
+            interpretation,
+        }]);
+    }
+
+    let mut steps: Vec<UtteranceStep> = Vec::new();
+    for (separator, piece) in pieces {
+        let error = match parse_intent_with(&piece, options) {
+            Ok(interpretation) => {
+                steps.push(UtteranceStep {
+                    text: piece,
+                    interpretation,
+                });
+                continue;
+            }
+            Err(error) => error,
+        };
+
+        let Some(previous) = steps.last_mut() else {
+            return Err(error);
+        };
+
+        // "open safari and slack" -> "open slack", but "search for salt and
+        // pepper" is a single query
+        let is_search = matches!(previous.interpretation.action, Action::WebSearch { .. });
+        if let Some(verb) = previous.text.split_whitespace().next().filter(|_| !is_search) {
+            let with_verb = format!("{} {}", verb, piece);
+            if let Ok(interpretation) = parse_intent_with(&with_verb, options) {
+                steps.push(UtteranceStep {
+                    text: with_verb,
+                    interpretation,
+                });
+                continue;
+            }
+        }
+
+        // "set volume to one hundred and five" was never two commands
+        let joined = format!("{}{}{}", previous.text, separator, piece);
+        match parse_intent_with(&joined, options) {
+            Ok(interpretation) => {
+                *previous = UtteranceStep {
+                    text: joined,
+                    interpretation,
+                }
+            }
+            Err(_) => return Err(error),
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Split input into pieces, each paired with the separator before it.
+fn split_pieces(input: &str) -> Vec<(&'static str, String)> {
+    let mut pieces = Vec::new();
+    let mut rest = input.trim();
+    let mut separator = "";
+
+    loop {
+        let lowered = rest.to_lowercase();
+        let next = SEPARATORS
+            .iter()
+            .filter_map(|sep| lowered.find(sep).map(|at| (at, *sep)))
+            .min_by_key(|(at, sep)| (*at, std::cmp::Reverse(sep.len())));
+
+        match next {
+            // Lowercasing can change byte lengths for non-ASCII input; only
+            // split where the offsets are valid in the original text
+            Some((at, sep)) if rest.is_char_boundary(at) && rest.is_char_boundary(at + sep.len()) => {
+                push_piece(&mut pieces, separator, &rest[..at]);
+                separator = sep;
+                rest = &rest[at + sep.len()..];
+            }
+            _ => {
+                push_piece(&mut pieces, separator, rest);
+                return pieces;
+            }
+        }
+    }
+}
+
+/// Add a non-empty piece, ignoring stray separators.
+fn push_piece(pieces: &mut Vec<(&'static str, String)>, separator: &'static str, piece: &str) {
+    let mut piece = piece.trim().trim_end_matches([',', ';']).trim();
+
+    // "mute, then open spotify" leaves "then" at the start of the piece
+    while let Some(connector) = ["and ", "then ", "after that "]
+        .into_iter()
+        .find(|c| piece.get(..c.len()).is_some_and(|start| start.eq_ignore_ascii_case(c)))
+    {
+        piece = piece[connector.len()..].trim_start();
+    }
+
+    if !piece.is_empty() {
+        pieces.push((separator, piece.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(input: &str) -> Vec<Action> {
+        parse_utterance(input, &ParseOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|step| step.interpretation.action)
+            .collect()
+    }
+
+    #[test]
+    fn test_single_command() {
+        assert_eq!(
+            actions("open safari"),
+            vec![Action::OpenApp { name: "Safari".to_string() }]
+        );
+    }
+
+    #[test]
+    fn test_and() {
+        assert_eq!(
+            actions("open safari and set volume to 30"),
+            vec![
+                Action::OpenApp { name: "Safari".to_string() },
+                Action::SetVolume { level: 30 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_then_and_commas() {
+        assert_eq!(
+            actions("mute, then open spotify and then unmute"),
+            vec![
+                Action::Mute,
+                Action::OpenApp { name: "Spotify".to_string() },
+                Action::Unmute,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shared_verb() {
+        assert_eq!(
+            actions("open safari and slack"),
+            vec![
+                Action::OpenApp { name: "Safari".to_string() },
+                Action::OpenApp { name: "Slack".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_separator_inside_number() {
+        let steps = parse_utterance("set volume to ninety and five", &ParseOptions::default()).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].text, "set volume to ninety and five");
+        assert_eq!(steps[0].interpretation.action, Action::SetVolume { level: 95 });
+    }
+
+    #[test]
+    fn test_search_query_with_and() {
+        assert_eq!(
+            actions("search for salt and pepper and then mute"),
+            vec![
+                Action::WebSearch { query: "salt and pepper".to_string(), engine: "duckduckgo".to_string() },
+                Action::Mute,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_text() {
+        let steps = parse_utterance("open Safari and mute", &ParseOptions::default()).unwrap();
+        let texts: Vec<_> = steps.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["open Safari", "mute"]);
+    }
+
+    #[test]
+    fn test_unparseable_piece() {
+        assert!(matches!(
+            parse_utterance("mute and do something random", &ParseOptions::default()),
+            Err(ParseError::UnrecognizedCommand(_))
+        ));
+    }
+}
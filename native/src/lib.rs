@@ -0,0 +1,178 @@
+//! Luna - Natural language system control for macOS and Linux
+//!
+//! The library behind the `luna` command: it parses natural language
+//! commands into `Action`s and turns them into macOS or Linux system
+//! commands. `Luna` is the stable entry point; the modules are public for
+//! callers that need the pieces (the parser on its own, a custom
+//! `CommandRunner`, the output formats).
+//!
+//! ```
+//! use luna::{Action, Config, Luna};
+//!
+//! let luna = Luna::new(Config::default());
+//! let steps = luna.parse("mute and set volume to 30").unwrap();
+//! assert_eq!(steps[1].interpretation.action, Action::SetVolume { level: 30 });
+//!
+//! // The command that would run, without running it
+//! let plan = luna.plan(&steps[1].interpretation.action);
+//! assert!(!plan.argv().is_empty());
+//! ```
+
+pub mod catalog;
+pub mod config;
+pub mod daemon;
+pub mod exec;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod intent;
+pub mod mcp;
+pub mod output;
+pub mod speech;
+
+pub use exec::{CommandPlan, ExecError, ExecResult, Executor};
+pub use intent::{Action, Interpretation, ParseError, UtteranceStep};
+
+use crate::catalog::AppCatalog;
+use crate::config::{AliasTable, ConfigError, SearchEngines, Snippets};
+use crate::exec::{LinuxExecutor, MacosExecutor, SystemRunner};
+use crate::intent::{parse_intent_ranked, parse_utterance, ParseOptions};
+
+/// What Luna knows about the user: their config files and installed apps.
+///
+/// `Config::default()` uses the built-in defaults and reads nothing from disk.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Spoken-name aliases (aliases.toml)
+    pub aliases: AliasTable,
+    /// Search engines that "search <engine> for ..." can name (search.toml)
+    pub search: SearchEngines,
+    /// Named text for "copy my <name>" (snippets.toml)
+    pub snippets: Snippets,
+    /// Installed apps that spoken names resolve against
+    pub catalog: AppCatalog,
+}
+
+impl Config {
+    /// Load the user's config files and scan the installed apps.
+    ///
+    /// Missing files fall back to their defaults; a file that can't be read
+    /// or parsed is an error.
+    pub fn load() -> Result<Self, ConfigError> {
+        Ok(Config {
+            aliases: AliasTable::load_default()?,
+            search: SearchEngines::load_default()?,
+            snippets: Snippets::load_default()?,
+            catalog: AppCatalog::load_default(),
+        })
+    }
+}
+
+/// Parses commands and runs them with the current platform's executor.
+///
+/// A `Luna` is `Send + Sync`, so one instance can serve many threads.
+pub struct Luna {
+    options: ParseOptions,
+    executor: Box<dyn Executor>,
+}
+
+impl Luna {
+    /// Create a `Luna` that runs real commands on this platform.
+    pub fn new(config: Config) -> Self {
+        let executor: Box<dyn Executor> = if cfg!(target_os = "linux") {
+            Box::new(
+                LinuxExecutor::with_runner(SystemRunner)
+                    .with_catalog(config.catalog.clone())
+                    .with_search_engines(config.search.clone()),
+            )
+        } else {
+            Box::new(MacosExecutor::with_runner(SystemRunner).with_search_engines(config.search.clone()))
+        };
+        Self::with_executor(config, executor)
+    }
+
+    /// Create a `Luna` that plans and runs actions with `executor`.
+    pub fn with_executor(config: Config, executor: Box<dyn Executor>) -> Self {
+        let mut options = ParseOptions {
+            aliases: config.aliases,
+            catalog: config.catalog,
+            search: config.search,
+            snippets: config.snippets,
+            ..ParseOptions::default()
+        };
+        // Installed app names join the built-in list for fuzzy matching
+        for name in options.catalog.names() {
+            if !options.known_apps.iter().any(|app| app.eq_ignore_ascii_case(&name)) {
+                options.known_apps.push(name);
+            }
+        }
+        Luna { options, executor }
+    }
+
+    /// Parse a command into its steps: one for a single action, several
+    /// for a compound command ("mute and open slack").
+    pub fn parse(&self, input: &str) -> Result<Vec<UtteranceStep>, ParseError> {
+        parse_utterance(input, &self.options)
+    }
+
+    /// Up to `limit` interpretations of a single command, most confident first.
+    pub fn parse_ranked(&self, input: &str, limit: usize) -> Result<Vec<Interpretation>, ParseError> {
+        parse_intent_ranked(input, &self.options, limit)
+    }
+
+    /// The command that implements an action, without running it.
+    pub fn plan(&self, action: &Action) -> CommandPlan {
+        self.executor.plan(action)
+    }
+
+    /// Run an action.
+    ///
+    /// Actions with a `confirmation_prompt` run without asking; confirming
+    /// them is up to the caller.
+    pub fn execute(&self, action: &Action) -> Result<ExecResult, ExecError> {
+        self.executor.execute(action)
+    }
+
+    /// The options commands are parsed with.
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{CommandOutput, RecordingRunner};
+
+    fn luna(config: Config, runner: &'static RecordingRunner) -> Luna {
+        Luna::with_executor(config, Box::new(LinuxExecutor::with_runner(runner)))
+    }
+
+    #[test]
+    fn test_parse_plan_execute() {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(RecordingRunner::new()));
+        let luna = luna(Config::default(), runner);
+
+        let steps = luna.parse("open firefox").unwrap();
+        let action = &steps[0].interpretation.action;
+        assert_eq!(luna.plan(action).to_string(), "gtk-launch firefox");
+        assert!(runner.calls().is_empty());
+
+        runner.push_output(CommandOutput::success(""));
+        assert!(luna.execute(action).unwrap().success);
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_config_reaches_the_parser() {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(RecordingRunner::new()));
+        let mut config = Config::default();
+        config.aliases.add("code", "Visual Studio Code");
+        let luna = luna(config, runner);
+
+        assert_eq!(
+            luna.parse("open code").unwrap()[0].interpretation.action,
+            Action::OpenApp { name: "Visual Studio Code".to_string() }
+        );
+        assert!(luna.parse_ranked("opne safari", 2).unwrap()[0].confidence < 1.0);
+    }
+}
@@ -1,24 +1,31 @@
-//! Luna - Natural language system control for macOS
+//! Luna - Natural language system control for macOS and Linux
 //!
-//! A CLI tool that accepts natural language commands and executes
-//! corresponding macOS system actions.
+//! The `luna` command: a thin client of the `luna` library that accepts
+//! natural language commands and executes corresponding macOS or Linux
+//! system actions.
 
-pub mod cli;
-pub mod exec;
-pub mod intent;
-pub mod output;
+mod cli;
+mod repl;
 
 use anyhow::Result;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::Arc;
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 
-use crate::cli::Cli;
-use crate::exec::{execute, get_command_string};
-use crate::intent::parse_intent;
-use crate::output::{
-    print_dry_run_human, print_dry_run_json, print_error_human, print_error_json, print_human,
-    print_json,
+use crate::cli::{AliasCommand, Cli, Command};
+use luna::catalog::AppCatalog;
+use luna::config::{AliasTable, SearchEngines, Snippets};
+use luna::daemon::{self, default_socket_path, DaemonError, Handler};
+use luna::mcp;
+use luna::output::{
+    print_dry_run_human, print_error_human, print_error_json, print_human, print_json,
+    print_needs_confirmation_human, print_sequence_json, print_skipped_human,
+    print_step_header_human, JsonOutput,
 };
+use luna::speech::{default_speaker, failure_phrase, result_phrase, Speaker, NOT_UNDERSTOOD_PHRASE};
+use luna::{Config, Interpretation, Luna, ParseError, UtteranceStep};
 
 fn main() {
     // Initialize tracing (only shows errors by default)
@@ -40,54 +47,356 @@ fn main() {
 fn run() -> Result<(), i32> {
     // Parse CLI arguments
     let cli = Cli::parse_args();
-    let input = &cli.command;
 
-    // Parse intent from natural language
-    let action = match parse_intent(input) {
-        Ok(action) => action,
+    let input = match (&cli.subcommand, &cli.command) {
+        (Some(Command::Alias(command)), _) => return run_alias(&cli, command),
+        (Some(Command::Repl), _) | (None, None) => return repl::run(&cli),
+        (Some(Command::Daemon { socket }), _) => return run_daemon(socket.as_deref()),
+        (Some(Command::Client { input, parse, socket }), _) => {
+            return run_client(&cli, input, *parse, socket.as_deref())
+        }
+        (Some(Command::Mcp), _) => return run_mcp(&cli),
+        #[cfg(feature = "http")]
+        (Some(Command::Serve { address, port, token, openapi }), _) => {
+            return run_serve(*address, *port, token.clone(), *openapi)
+        }
+        (None, Some(input)) => input,
+    };
+
+    let speaker = if cli.speak { load_speaker() } else { None };
+    let speaker = speaker.as_deref();
+
+    // Parse intent from natural language; a compound utterance yields several steps
+    let luna = load_luna();
+    let steps = match luna.parse(input) {
+        Ok(steps) => steps,
         Err(e) => {
-            if cli.json {
-                print_error_json(input, &e.to_string());
-            } else {
-                print_error_human(input, &e.to_string());
-            }
+            report_parse_error(&cli, input, &e, speaker);
             return Err(1);
         }
     };
 
+    run_steps(&cli, &luna, input, &steps, speaker)
+}
+
+/// Print (and, with `--speak`, speak) an input that could not be parsed.
+fn report_parse_error(cli: &Cli, input: &str, error: &ParseError, speaker: Option<&dyn Speaker>) {
+    if cli.json {
+        print_error_json(input, &error.to_string());
+    } else {
+        print_error_human(input, &error.to_string());
+    }
+    match error {
+        ParseError::UnrecognizedCommand(_) => speak(speaker, NOT_UNDERSTOOD_PHRASE),
+        e => speak(speaker, &e.to_string()),
+    }
+}
+
+/// Run the steps parsed from one input: a single action on its own, or a
+/// compound utterance as a sequence.
+fn run_steps(
+    cli: &Cli,
+    luna: &Luna,
+    input: &str,
+    steps: &[UtteranceStep],
+    speaker: Option<&dyn Speaker>,
+) -> Result<(), i32> {
+    if let [step] = steps {
+        let (output, ok) = run_step(cli, luna, input, &step.interpretation, speaker);
+        if cli.json {
+            print_json(&output);
+        }
+        return if ok { Ok(()) } else { Err(1) };
+    }
+
+    run_sequence(cli, luna, input, steps, speaker)
+}
+
+/// The speaker for `--speak`, warning if no text-to-speech program is installed.
+fn load_speaker() -> Option<Box<dyn Speaker>> {
+    let speaker = default_speaker();
+    if speaker.is_none() {
+        eprintln!("Warning: --speak needs `say` (macOS) or `espeak-ng` (Linux); staying silent");
+    }
+    speaker
+}
+
+/// Read `text` aloud if `--speak` was given; a speech failure only warns.
+fn speak(speaker: Option<&dyn Speaker>, text: &str) {
+    if let Some(speaker) = speaker {
+        if let Err(e) = speaker.speak(text) {
+            eprintln!("Warning: could not speak: {}", e);
+        }
+    }
+}
+
+/// Run each step of a compound utterance in order.
+///
+/// After a failure the remaining steps are skipped unless `--keep-going`
+/// was given.
+fn run_sequence(
+    cli: &Cli,
+    luna: &Luna,
+    input: &str,
+    steps: &[UtteranceStep],
+    speaker: Option<&dyn Speaker>,
+) -> Result<(), i32> {
+    let mut outputs = Vec::with_capacity(steps.len());
+    let mut failed = false;
+
+    for (index, step) in steps.iter().enumerate() {
+        if !cli.json {
+            print_step_header_human(index, steps.len());
+        }
+
+        if failed && !cli.keep_going {
+            if !cli.json {
+                print_skipped_human(&step.text, &step.interpretation);
+            }
+            outputs.push(JsonOutput::skipped(&step.text, &step.interpretation));
+            continue;
+        }
+
+        let (output, ok) = run_step(cli, luna, &step.text, &step.interpretation, speaker);
+        failed |= !ok;
+        outputs.push(output);
+    }
+
+    if cli.json {
+        print_sequence_json(input, outputs);
+    }
+
+    if failed {
+        Err(1)
+    } else {
+        Ok(())
+    }
+}
+
+/// Dry-run or execute a single action.
+///
+/// Human output is printed (and, with `--speak`, spoken) immediately; JSON
+/// output, including the ranked candidate interpretations, is returned so
+/// the caller can print it alone or as part of a sequence. The flag is false
+/// if the step failed.
+fn run_step(
+    cli: &Cli,
+    luna: &Luna,
+    input: &str,
+    interpretation: &Interpretation,
+    speaker: Option<&dyn Speaker>,
+) -> (JsonOutput, bool) {
+    let action = &interpretation.action;
+    let candidates = if cli.json {
+        luna.parse_ranked(input, cli.candidates).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
     // Handle dry-run mode
     if cli.dry_run {
-        let command = get_command_string(&action);
-        if cli.json {
-            print_dry_run_json(input, &action, &command);
-        } else {
-            print_dry_run_human(input, &action, &command);
+        let command = luna.plan(action);
+        if !cli.json {
+            print_dry_run_human(input, interpretation, &command);
+        }
+        let output = JsonOutput::dry_run(input, interpretation, &command);
+        return (output.with_candidates(candidates), true);
+    }
+
+    // Actions that are hard to undo run only with --yes or a "y" at the prompt
+    if let Some(prompt) = action.confirmation_prompt() {
+        let confirmed = cli.yes || (!cli.json && confirm(&prompt));
+        if !confirmed {
+            if !cli.json {
+                print_needs_confirmation_human(input, interpretation, &prompt);
+            }
+            speak(speaker, &prompt);
+            let command = luna.plan(action);
+            let output = JsonOutput::needs_confirmation(input, interpretation, &command, &prompt);
+            return (output.with_candidates(candidates), false);
         }
-        return Ok(());
     }
 
     // Execute the action
-    match execute(&action) {
+    match luna.execute(action) {
         Ok(result) => {
-            if cli.json {
-                print_json(input, &action, &result);
-            } else {
-                print_human(input, &action, &result);
+            if !cli.json {
+                print_human(input, interpretation, &result);
+            }
+            speak(speaker, &result_phrase(action, &result));
+            let output = JsonOutput::executed(input, interpretation, &result);
+            (output.with_candidates(candidates), result.success)
+        }
+        Err(e) => {
+            if !cli.json {
+                print_error_human(input, &e.to_string());
             }
+            speak(speaker, &failure_phrase(action));
+            (JsonOutput::error(input, &e.to_string()), false)
+        }
+    }
+}
 
-            if result.success {
-                Ok(())
-            } else {
-                Err(1)
+/// Ask the user to confirm on the terminal; false if stdin isn't a terminal.
+fn confirm(prompt: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+
+    eprint!("{} [y/N] ", prompt);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// A `Luna` with the user's aliases, search engines and snippets and the
+/// installed app catalog.
+///
+/// Unlike `Config::load`, an invalid config file only warns.
+fn load_luna() -> Luna {
+    Luna::new(Config {
+        aliases: load_aliases(),
+        search: SearchEngines::load_default().unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring search engines: {}", e);
+            SearchEngines::default()
+        }),
+        snippets: Snippets::load_default().unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring snippets: {}", e);
+            Snippets::default()
+        }),
+        catalog: AppCatalog::load_default(),
+    })
+}
+
+/// Load the user's alias table, warning (but carrying on) if it is invalid.
+fn load_aliases() -> AliasTable {
+    AliasTable::load_default().unwrap_or_else(|e| {
+        eprintln!("Warning: ignoring aliases: {}", e);
+        AliasTable::default()
+    })
+}
+
+/// Run `luna daemon` until it is killed.
+fn run_daemon(socket: Option<&Path>) -> Result<(), i32> {
+    let fail = |e: &dyn std::fmt::Display| {
+        eprintln!("Error: {}", e);
+        1
+    };
+
+    let path = socket.map(Path::to_path_buf).unwrap_or_else(default_socket_path);
+    let listener = daemon::bind(&path).map_err(|e| fail(&e))?;
+    let handler = Handler::new(load_luna());
+    eprintln!("Luna daemon listening on {}", path.display());
+    daemon::serve(listener, Arc::new(handler)).map_err(|e| fail(&DaemonError::from(e)))
+}
+
+/// Run `luna mcp` until the client closes stdin.
+fn run_mcp(cli: &Cli) -> Result<(), i32> {
+    let policy = mcp::Policy { dry_run: cli.dry_run, yes: cli.yes };
+    let server = mcp::Server::new(load_luna(), policy);
+    mcp::serve(&server, io::stdin().lock(), io::stdout().lock()).map_err(|e| {
+        eprintln!("Error: {}", e);
+        1
+    })
+}
+
+/// Run `luna serve` until it is killed.
+#[cfg(feature = "http")]
+fn run_serve(address: std::net::IpAddr, port: u16, token: Option<String>, openapi: bool) -> Result<(), i32> {
+    use luna::http::{self, Api, ApiDoc, TOKEN_ENV};
+    use utoipa::OpenApi;
+
+    if openapi {
+        if let Ok(json) = ApiDoc::openapi().to_pretty_json() {
+            println!("{}", json);
+        }
+        return Ok(());
+    }
+
+    let token = token.or_else(|| std::env::var(TOKEN_ENV).ok()).filter(|t| !t.is_empty());
+    let address = std::net::SocketAddr::new(address, port);
+    let server = http::bind(address, token.as_deref()).map_err(|e| {
+        eprintln!("Error: {}", e);
+        1
+    })?;
+    let api = Api::new(Handler::new(load_luna()), token);
+    eprintln!("Luna HTTP API listening on http://{}", address);
+    http::serve(server, Arc::new(api));
+    Ok(())
+}
+
+/// Run `luna client`: send one command to the daemon and print the reply.
+///
+/// The reply is always JSON; the exit status is non-zero unless every step
+/// succeeded (or was parsed or planned).
+fn run_client(cli: &Cli, input: &str, parse: bool, socket: Option<&Path>) -> Result<(), i32> {
+    let path = socket.map(Path::to_path_buf).unwrap_or_else(default_socket_path);
+    let method = match (parse, cli.dry_run) {
+        (true, _) => "parse",
+        (false, true) => "dry_run",
+        (false, false) => "execute",
+    };
+    let params = serde_json::json!({
+        "input": input,
+        "yes": cli.yes,
+        "keep_going": cli.keep_going,
+        "candidates": cli.candidates,
+    });
+
+    let result = daemon::call(&path, method, params).map_err(|e| {
+        eprintln!("Error: {}", e);
+        1
+    })?;
+    if let Ok(json) = serde_json::to_string_pretty(&result) {
+        println!("{}", json);
+    }
+    match result["status"].as_str() {
+        Some("success" | "dry-run" | "parsed") => Ok(()),
+        _ => Err(1),
+    }
+}
+
+/// Run a `luna alias` subcommand.
+fn run_alias(cli: &Cli, command: &AliasCommand) -> Result<(), i32> {
+    let fail = |e: &dyn std::fmt::Display| {
+        eprintln!("Error: {}", e);
+        1
+    };
+
+    let path = AliasTable::default_path().map_err(|e| fail(&e))?;
+    let mut table = AliasTable::load(&path).map_err(|e| fail(&e))?;
+
+    match command {
+        AliasCommand::Add { alias, target } => {
+            match table.add(alias, target) {
+                Some(previous) => println!("Updated alias: {} -> {} (was {})", alias, target, previous),
+                None => println!("Added alias: {} -> {}", alias, target),
             }
+            table.save(&path).map_err(|e| fail(&e))
         }
-        Err(e) => {
+        AliasCommand::Remove { alias } => match table.remove(alias) {
+            Some(target) => {
+                println!("Removed alias: {} -> {}", alias, target);
+                table.save(&path).map_err(|e| fail(&e))
+            }
+            None => Err(fail(&format!("No alias named \"{}\"", alias))),
+        },
+        AliasCommand::List => {
             if cli.json {
-                print_error_json(input, &e.to_string());
+                if let Ok(json) = serde_json::to_string_pretty(&table.apps) {
+                    println!("{}", json);
+                }
+            } else if table.apps.is_empty() {
+                println!("No aliases defined. Add one with: luna alias add code \"Visual Studio Code\"");
             } else {
-                print_error_human(input, &e.to_string());
+                for (alias, target) in &table.apps {
+                    println!("{} -> {}", alias, target);
+                }
             }
-            Err(1)
+            Ok(())
         }
     }
 }
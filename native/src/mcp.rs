@@ -0,0 +1,434 @@
+//! MCP server for Luna.
+//!
+//! `luna mcp` speaks the Model Context Protocol over stdio (JSON-RPC 2.0, one
+//! message per line) so LLM agents can drive Luna. Each `Action` variant is a
+//! tool ("open_app", "set_volume", ...) whose input schema is the variant's
+//! `params`, taken from the schema derived from the serde definition of
+//! `Action`; arguments are deserialized through that same definition.
+//!
+//! Calls follow the CLI's policy: with `--dry-run` a tool only reports the
+//! command it would run, and actions that need confirmation run only if the
+//! server was started with `--yes`, since there is nobody to ask.
+
+use crate::daemon::{Response, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR};
+use crate::exec::ExecResult;
+use crate::intent::parse::ACTION_EXAMPLES;
+use crate::intent::{parse_intent, Action, Interpretation};
+use crate::output::JsonOutput;
+use crate::speech::result_phrase;
+use crate::Luna;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use utoipa::PartialSchema;
+
+/// The protocol version offered when the client asks for one we don't know.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Protocol versions this server can speak, newest first.
+const SUPPORTED_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// How tool calls are allowed to act.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Policy {
+    /// Only report the command each call would run
+    pub dry_run: bool,
+    /// Run actions that need confirmation
+    pub yes: bool,
+}
+
+/// A tool offered by `tools/list`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+    pub annotations: ToolAnnotations,
+    /// The `Action` variant the tool runs
+    #[serde(skip)]
+    pub kind: String,
+}
+
+/// Hints about what a tool does.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    pub title: String,
+    /// The tool only reports state
+    pub read_only_hint: bool,
+    /// The tool is hard to undo (it needs confirmation)
+    pub destructive_hint: bool,
+}
+
+/// One tool per `Action` variant, in declaration order.
+pub fn tools() -> Vec<Tool> {
+    let schema = serde_json::to_value(Action::schema()).unwrap_or_default();
+    let samples: Vec<Action> = ACTION_EXAMPLES.iter().filter_map(|example| parse_intent(example).ok()).collect();
+
+    let variants = schema["oneOf"].as_array().cloned().unwrap_or_default();
+    variants
+        .iter()
+        .filter_map(|variant| {
+            let kind = variant["properties"]["type"]["enum"][0].as_str()?;
+            let sample = samples.iter().find(|action| action.kind() == kind);
+
+            let mut input_schema = variant["properties"]["params"].clone();
+            match input_schema.as_object_mut() {
+                // The description is the tool's own
+                Some(params) => {
+                    params.remove("description");
+                }
+                None => input_schema = json!({ "type": "object", "properties": {} }),
+            }
+
+            Some(Tool {
+                name: snake_case(kind),
+                description: variant["description"].as_str().unwrap_or(kind).to_string(),
+                input_schema,
+                annotations: ToolAnnotations {
+                    title: kind.to_string(),
+                    read_only_hint: matches!(sample, Some(Action::NowPlaying | Action::ReadClipboard)),
+                    destructive_hint: sample.is_some_and(|action| action.confirmation_prompt().is_some()),
+                },
+                kind: kind.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// "SetVolume" -> "set_volume".
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Answers MCP messages with one `Luna`.
+pub struct Server {
+    luna: Luna,
+    policy: Policy,
+    tools: Vec<Tool>,
+}
+
+impl Server {
+    pub fn new(luna: Luna, policy: Policy) -> Self {
+        Server { luna, policy, tools: tools() }
+    }
+
+    /// Answer one line from the client; notifications get no response.
+    pub fn handle_line(&self, line: &str) -> Option<Response> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => return Some(Response::error(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses from the client need no answer
+            return match id {
+                Some(_) if message.get("result").is_some() || message.get("error").is_some() => None,
+                id => Some(Response::error(id.unwrap_or_default(), INVALID_REQUEST, "Missing method")),
+            };
+        };
+        let params = message.get("params").cloned().unwrap_or_default();
+
+        let outcome = self.handle(method, &params);
+        let id = id?;
+        Some(match outcome {
+            Ok(result) => Response::result(id, result),
+            Err((code, message)) => Response::error(id, code, message),
+        })
+    }
+
+    /// Answer a request (or notification) by method name.
+    fn handle(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools })),
+            "tools/call" => {
+                let name = params["name"].as_str().ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+                let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                self.call_tool(name, arguments)
+            }
+            method if method.starts_with("notifications/") => Ok(Value::Null),
+            method => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params["protocolVersion"].as_str().unwrap_or_default();
+        let version = SUPPORTED_VERSIONS.iter().find(|v| **v == requested).unwrap_or(&PROTOCOL_VERSION);
+
+        let mut instructions = String::from(
+            "Each tool runs one system action on the user's computer. Tools marked destructive \
+             run only if the user started the server with --yes.",
+        );
+        if self.policy.dry_run {
+            instructions.push_str(" Dry-run mode: tools report the command they would run without running it.");
+        }
+
+        json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": { "name": "luna", "version": env!("CARGO_PKG_VERSION") },
+            "instructions": instructions,
+        })
+    }
+
+    /// Run a tool, following the dry-run and confirmation policy.
+    ///
+    /// An unknown tool is a protocol error; bad arguments and failed actions
+    /// are results with `isError` set, so the agent can see what went wrong.
+    fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, (i64, String)> {
+        let tool = self.tools.iter().find(|tool| tool.name == name);
+        let tool = tool.ok_or((INVALID_PARAMS, format!("Unknown tool: {}", name)))?;
+
+        let message = if tool.input_schema["properties"].as_object().is_some_and(|p| p.is_empty()) {
+            json!({ "type": tool.kind })
+        } else {
+            json!({ "type": tool.kind, "params": arguments })
+        };
+        let action = match serde_json::from_value::<Action>(message).map_err(|e| e.to_string()) {
+            Ok(action) => match check_ranges(&action) {
+                Ok(()) => action,
+                Err(e) => return Ok(error_result(&format!("Invalid arguments: {}", e))),
+            },
+            Err(e) => return Ok(error_result(&format!("Invalid arguments: {}", e))),
+        };
+
+        let input = action.to_string();
+        let interpretation =
+            Interpretation { action: action.clone(), confidence: 1.0, rule: "mcp", corrections: Vec::new() };
+
+        if self.policy.dry_run {
+            let command = self.luna.plan(&action);
+            let output = JsonOutput::dry_run(&input, &interpretation, &command);
+            return Ok(result(&format!("Dry run: would run `{}`", command), &output, false));
+        }
+
+        if let Some(prompt) = action.confirmation_prompt() {
+            if !self.policy.yes {
+                let command = self.luna.plan(&action);
+                let output = JsonOutput::needs_confirmation(&input, &interpretation, &command, &prompt);
+                let text = format!(
+                    "Not run: {} The user must start `luna mcp --yes` to allow actions that need confirmation.",
+                    prompt
+                );
+                return Ok(result(&text, &output, true));
+            }
+        }
+
+        Ok(match self.luna.execute(&action) {
+            Ok(exec) => tool_result(&action, &interpretation, &exec),
+            Err(e) => error_result(&e.to_string()),
+        })
+    }
+}
+
+/// Levels are 0-100 and changes at most 100 either way, as the schema says.
+fn check_ranges(action: &Action) -> Result<(), String> {
+    match action {
+        Action::SetVolume { level } | Action::SetBrightness { level } if *level > 100 => {
+            Err(format!("level must be 0-100, got {}", level))
+        }
+        Action::AdjustVolume { delta } | Action::AdjustBrightness { delta } if !(-100..=100).contains(delta) => {
+            Err(format!("delta must be -100 to 100, got {}", delta))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The result of a tool that ran: a sentence about what happened, plus the
+/// `JsonOutput` of the run as structured content.
+fn tool_result(action: &Action, interpretation: &Interpretation, exec: &ExecResult) -> Value {
+    let output = JsonOutput::executed(&action.to_string(), interpretation, exec);
+    let mut text = result_phrase(action, exec);
+    if let Some(ref out) = exec.output {
+        if !exec.success && !out.trim().is_empty() {
+            text = format!("{}: {}", text, out.trim());
+        }
+    }
+    result(&text, &output, !exec.success)
+}
+
+/// A tool result; the structured content is repeated as JSON text for
+/// clients that don't read it.
+fn result(text: &str, output: &JsonOutput, is_error: bool) -> Value {
+    let structured = serde_json::to_value(output).unwrap_or_default();
+    json!({
+        "content": [
+            { "type": "text", "text": text },
+            { "type": "text", "text": structured.to_string() },
+        ],
+        "structuredContent": structured,
+        "isError": is_error,
+    })
+}
+
+/// A tool result for a call that could not run.
+fn error_result(message: &str) -> Value {
+    json!({ "content": [{ "type": "text", "text": message }], "isError": true })
+}
+
+/// Answer messages from `input` on `output` until end of input.
+pub fn serve(server: &Server, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line) {
+            writeln!(output, "{}", serde_json::to_string(&response)?)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::linux::AudioTool;
+    use crate::exec::{CommandOutput, LinuxExecutor, RecordingRunner};
+    use crate::Config;
+
+    fn new_server(policy: Policy) -> (Server, &'static RecordingRunner) {
+        let runner: &'static RecordingRunner = Box::leak(Box::new(RecordingRunner::new()));
+        let executor = LinuxExecutor::with_runner(runner).with_audio_tool(AudioTool::Wpctl);
+        (Server::new(Luna::with_executor(Config::default(), Box::new(executor)), policy), runner)
+    }
+
+    fn call(server: &Server, name: &str, arguments: Value) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": { "name": name, "arguments": arguments },
+        });
+        let response = server.handle_line(&request.to_string()).unwrap();
+        assert_eq!(response.error, None);
+        response.result.unwrap()
+    }
+
+    #[test]
+    fn test_tools_from_action_schema() {
+        let tools = tools();
+        assert_eq!(tools.len(), ACTION_EXAMPLES.len());
+
+        let tool = |name: &str| tools.iter().find(|tool| tool.name == name).unwrap();
+        assert_eq!(tool("open_app").input_schema["required"], json!(["name"]));
+        assert_eq!(tool("set_volume").input_schema["properties"]["level"]["maximum"], 100);
+        assert_eq!(tool("set_volume").description, "Set the system volume to a specific level (0-100)");
+        assert_eq!(tool("mute").input_schema, json!({ "type": "object", "properties": {} }));
+        assert!(tool("transform_clipboard").input_schema["properties"]["transform"]["enum"].is_array());
+
+        assert!(tool("restart").annotations.destructive_hint);
+        assert!(tool("read_clipboard").annotations.read_only_hint);
+        assert!(!tool("open_app").annotations.destructive_hint);
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("SetVolume"), "set_volume");
+        assert_eq!(snake_case("Mute"), "mute");
+    }
+
+    #[test]
+    fn test_initialize_and_notifications() {
+        let (server, _) = new_server(Policy::default());
+        let request = r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#;
+        let result = server.handle_line(request).unwrap().result.unwrap();
+        assert_eq!(result["protocolVersion"], "2025-03-26");
+        assert_eq!(result["serverInfo"]["name"], "luna");
+
+        assert_eq!(server.handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#), None);
+
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+        let result = server.handle_line(request).unwrap().result.unwrap();
+        assert_eq!(result["tools"][0]["name"], "open_app");
+        assert!(result["tools"][0].get("kind").is_none());
+    }
+
+    #[test]
+    fn test_call_tool() {
+        let (server, runner) = new_server(Policy::default());
+        runner.push_output(CommandOutput::success(""));
+
+        let result = call(&server, "set_volume", json!({ "level": 30 }));
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["content"][0]["text"], "Volume set to 30");
+        assert_eq!(result["structuredContent"]["status"], "success");
+        assert_eq!(result["structuredContent"]["executed"], "wpctl set-volume @DEFAULT_AUDIO_SINK@ 30%");
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_call_tool_invalid_arguments() {
+        let (server, runner) = new_server(Policy::default());
+        for (name, arguments) in [
+            ("set_volume", json!({ "level": 150 })),
+            ("set_volume", json!({ "volume": 30 })),
+            ("transform_clipboard", json!({ "transform": "reverse" })),
+        ] {
+            let result = call(&server, name, arguments);
+            assert_eq!(result["isError"], true);
+            assert!(result["content"][0]["text"].as_str().unwrap().starts_with("Invalid arguments"));
+        }
+        assert!(runner.calls().is_empty());
+
+        let response = server.handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"nope"}}"#);
+        assert_eq!(response.unwrap().error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_confirmation_policy() {
+        let (server, runner) = new_server(Policy::default());
+        let result = call(&server, "restart", json!({}));
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["structuredContent"]["status"], "needs_confirmation");
+        assert!(runner.calls().is_empty());
+
+        let (server, runner) = new_server(Policy { yes: true, ..Policy::default() });
+        runner.push_output(CommandOutput::success(""));
+        let result = call(&server, "restart", json!({}));
+        assert_eq!(result["isError"], false);
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_dry_run_policy() {
+        let (server, runner) = new_server(Policy { dry_run: true, yes: true });
+        let result = call(&server, "shutdown", json!({}));
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["structuredContent"]["status"], "dry-run");
+        assert_eq!(result["content"][0]["text"], "Dry run: would run `systemctl poweroff`");
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let (server, _) = new_server(Policy::default());
+        assert_eq!(server.handle_line("{oops").unwrap().error.unwrap().code, PARSE_ERROR);
+        let response = server.handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#);
+        assert_eq!(response.unwrap().error.unwrap().code, METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_serve() {
+        let (server, _) = new_server(Policy::default());
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+            "\n\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        serve(&server, input.as_bytes(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n");
+    }
+}
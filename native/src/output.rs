@@ -2,29 +2,184 @@
 //!
 //! Handles both human-readable and JSON output formats.
 
-use crate::exec::ExecResult;
-use crate::intent::Action;
+use crate::exec::{CommandPlan, ExecResult, NowPlaying};
+use crate::intent::{Correction, Interpretation};
 use serde::Serialize;
+use utoipa::ToSchema;
 
 /// JSON output structure for machine-readable output.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct JsonOutput {
     pub input: String,
     pub intent: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub corrections: Vec<Correction>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<Interpretation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub executed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argv: Option<Vec<String>>,
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub now_playing: Option<NowPlaying>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
 
+impl JsonOutput {
+    /// Output for an action that was executed.
+    pub fn executed(input: &str, interpretation: &Interpretation, result: &ExecResult) -> Self {
+        JsonOutput {
+            input: input.to_string(),
+            intent: interpretation.action.to_string(),
+            confidence: Some(interpretation.confidence),
+            corrections: interpretation.corrections.clone(),
+            candidates: Vec::new(),
+            executed: Some(result.command.to_string()),
+            argv: Some(result.command.argv()),
+            status: if result.success { "success" } else { "failed" }.to_string(),
+            now_playing: result.now_playing.clone(),
+            clipboard: result.clipboard.clone(),
+            confirmation: None,
+            error: if result.success {
+                None
+            } else {
+                result.output.clone()
+            },
+        }
+    }
+
+    /// Output for an action shown in dry-run mode.
+    pub fn dry_run(input: &str, interpretation: &Interpretation, command: &CommandPlan) -> Self {
+        JsonOutput {
+            input: input.to_string(),
+            intent: interpretation.action.to_string(),
+            confidence: Some(interpretation.confidence),
+            corrections: interpretation.corrections.clone(),
+            candidates: Vec::new(),
+            executed: Some(command.to_string()),
+            argv: Some(command.argv()),
+            status: "dry-run".to_string(),
+            now_playing: None,
+            clipboard: None,
+            confirmation: None,
+            error: None,
+        }
+    }
+
+    /// Output for an action that was not run because it needs confirmation.
+    pub fn needs_confirmation(
+        input: &str,
+        interpretation: &Interpretation,
+        command: &CommandPlan,
+        prompt: &str,
+    ) -> Self {
+        JsonOutput {
+            status: "needs_confirmation".to_string(),
+            confirmation: Some(prompt.to_string()),
+            ..JsonOutput::dry_run(input, interpretation, command)
+        }
+    }
+
+    /// Output for an action that was only parsed, without planning a command.
+    pub fn parsed(input: &str, interpretation: &Interpretation) -> Self {
+        JsonOutput {
+            input: input.to_string(),
+            intent: interpretation.action.to_string(),
+            confidence: Some(interpretation.confidence),
+            corrections: interpretation.corrections.clone(),
+            candidates: Vec::new(),
+            executed: None,
+            argv: None,
+            status: "parsed".to_string(),
+            now_playing: None,
+            clipboard: None,
+            confirmation: None,
+            error: None,
+        }
+    }
+
+    /// Output for a step that was not run because an earlier step failed.
+    pub fn skipped(input: &str, interpretation: &Interpretation) -> Self {
+        JsonOutput {
+            status: "skipped".to_string(),
+            ..JsonOutput::parsed(input, interpretation)
+        }
+    }
+
+    /// Attach the ranked candidate interpretations of the input.
+    pub fn with_candidates(mut self, candidates: Vec<Interpretation>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    /// Output for input that could not be parsed or executed.
+    pub fn error(input: &str, error: &str) -> Self {
+        JsonOutput {
+            input: input.to_string(),
+            intent: "unknown".to_string(),
+            confidence: None,
+            corrections: Vec::new(),
+            candidates: Vec::new(),
+            executed: None,
+            argv: None,
+            status: "error".to_string(),
+            now_playing: None,
+            clipboard: None,
+            confirmation: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// JSON output structure for an utterance with several steps.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JsonSequenceOutput {
+    pub input: String,
+    pub status: String,
+    pub steps: Vec<JsonOutput>,
+}
+
+impl JsonSequenceOutput {
+    /// Combine per-step outputs; the overall status is "success" (or
+    /// "dry-run" or "parsed") only if every step has that status,
+    /// "needs_confirmation" if a step is waiting for confirmation and none
+    /// failed, otherwise "failed".
+    pub fn new(input: &str, steps: Vec<JsonOutput>) -> Self {
+        let has_status = |status: &str| steps.iter().any(|s| s.status == status);
+        let status = match steps.first().map(|step| step.status.as_str()) {
+            Some(first @ ("success" | "dry-run" | "parsed")) if steps.iter().all(|s| s.status == first) => first,
+            _ if has_status("needs_confirmation") && !has_status("failed") && !has_status("error") => {
+                "needs_confirmation"
+            }
+            _ => "failed",
+        };
+
+        JsonSequenceOutput {
+            input: input.to_string(),
+            status: status.to_string(),
+            steps,
+        }
+    }
+}
+
 /// Print the result of executing an action in human-readable format.
-pub fn print_human(input: &str, action: &Action, result: &ExecResult) {
+pub fn print_human(input: &str, interpretation: &Interpretation, result: &ExecResult) {
     println!("Input: \"{}\"", input);
-    println!("Intent: {}", action);
+    print_intent_human(interpretation);
     println!("Executed: {}", result.command);
     if result.success {
         println!("Result: success");
+        if let Some(ref output) = result.output {
+            println!("Output: {}", output.trim());
+        }
     } else {
         println!("Result: failed");
         if let Some(ref output) = result.output {
@@ -33,46 +188,55 @@ pub fn print_human(input: &str, action: &Action, result: &ExecResult) {
     }
 }
 
-/// Print the result of executing an action in JSON format.
-pub fn print_json(input: &str, action: &Action, result: &ExecResult) {
-    let output = JsonOutput {
-        input: input.to_string(),
-        intent: action.to_string(),
-        executed: Some(result.command.clone()),
-        status: if result.success { "success" } else { "failed" }.to_string(),
-        error: if result.success {
-            None
-        } else {
-            result.output.clone()
-        },
-    };
-
-    // Pretty print for readability
-    if let Ok(json) = serde_json::to_string_pretty(&output) {
-        println!("{}", json);
-    }
+/// Print the output of a single action in JSON format.
+pub fn print_json(output: &JsonOutput) {
+    print_json_value(output);
 }
 
 /// Print dry-run output in human-readable format.
-pub fn print_dry_run_human(input: &str, action: &Action, command: &str) {
+pub fn print_dry_run_human(input: &str, interpretation: &Interpretation, command: &CommandPlan) {
     println!("Input: \"{}\"", input);
-    println!("Intent: {}", action);
+    print_intent_human(interpretation);
     println!("Would execute: {}", command);
     println!("(dry-run mode - no action taken)");
 }
 
-/// Print dry-run output in JSON format.
-pub fn print_dry_run_json(input: &str, action: &Action, command: &str) {
-    let output = JsonOutput {
-        input: input.to_string(),
-        intent: action.to_string(),
-        executed: Some(command.to_string()),
-        status: "dry-run".to_string(),
-        error: None,
-    };
+/// Print an action that was not run because it needs confirmation.
+pub fn print_needs_confirmation_human(input: &str, interpretation: &Interpretation, prompt: &str) {
+    println!("Input: \"{}\"", input);
+    print_intent_human(interpretation);
+    println!("Needs confirmation: {}", prompt);
+    println!("Result: not run (re-run with --yes to confirm)");
+}
 
-    if let Ok(json) = serde_json::to_string_pretty(&output) {
-        println!("{}", json);
+/// Print the header for one step of a multi-step utterance.
+pub fn print_step_header_human(index: usize, total: usize) {
+    if index > 0 {
+        println!();
+    }
+    println!("Step {}/{}", index + 1, total);
+}
+
+/// Print a step that was skipped because an earlier step failed.
+pub fn print_skipped_human(input: &str, interpretation: &Interpretation) {
+    println!("Input: \"{}\"", input);
+    print_intent_human(interpretation);
+    println!("Result: skipped (an earlier step failed)");
+}
+
+/// Print the results of a multi-step utterance in JSON format.
+pub fn print_sequence_json(input: &str, steps: Vec<JsonOutput>) {
+    print_json_value(&JsonSequenceOutput::new(input, steps));
+}
+
+/// Print the parsed intent and any corrections applied to reach it.
+fn print_intent_human(interpretation: &Interpretation) {
+    println!("Intent: {}", interpretation.action);
+    if interpretation.needs_confirmation() {
+        println!("Confidence: {:.2} (low)", interpretation.confidence);
+    }
+    for correction in &interpretation.corrections {
+        println!("Corrected: {}", correction);
     }
 }
 
@@ -84,22 +248,24 @@ pub fn print_error_human(input: &str, error: &str) {
     eprintln!("Examples of valid commands:");
     eprintln!("  luna \"open safari\"");
     eprintln!("  luna \"open https://google.com\"");
+    eprintln!("  luna \"open downloads\"");
+    eprintln!("  luna \"search for rust lifetimes\"");
+    eprintln!("  luna \"quit spotify\"");
     eprintln!("  luna \"set volume to 50\"");
+    eprintln!("  luna \"volume up by 10\"");
     eprintln!("  luna \"mute\"");
     eprintln!("  luna \"unmute\"");
+    eprintln!("  luna \"open safari and set volume to 30\"");
 }
 
 /// Print an error in JSON format.
 pub fn print_error_json(input: &str, error: &str) {
-    let output = JsonOutput {
-        input: input.to_string(),
-        intent: "unknown".to_string(),
-        executed: None,
-        status: "error".to_string(),
-        error: Some(error.to_string()),
-    };
-
-    if let Ok(json) = serde_json::to_string_pretty(&output) {
+    print_json_value(&JsonOutput::error(input, error));
+}
+
+/// Pretty print a JSON value for readability.
+fn print_json_value<T: Serialize>(value: &T) {
+    if let Ok(json) = serde_json::to_string_pretty(value) {
         println!("{}", json);
     }
 }
@@ -107,14 +273,22 @@ pub fn print_error_json(input: &str, error: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intent::{parse_intent_ranked, ParseOptions};
 
     #[test]
     fn test_json_output_serialization() {
         let output = JsonOutput {
             input: "open safari".to_string(),
             intent: "OpenApp(name=\"Safari\")".to_string(),
-            executed: Some("open -a \"Safari\"".to_string()),
+            confidence: None,
+            corrections: Vec::new(),
+            candidates: Vec::new(),
+            executed: Some("open -a Safari".to_string()),
+            argv: Some(vec!["open".to_string(), "-a".to_string(), "Safari".to_string()]),
             status: "success".to_string(),
+            now_playing: None,
+            clipboard: None,
+            confirmation: None,
             error: None,
         };
 
@@ -129,8 +303,15 @@ mod tests {
         let output = JsonOutput {
             input: "invalid".to_string(),
             intent: "unknown".to_string(),
+            confidence: None,
+            corrections: Vec::new(),
+            candidates: Vec::new(),
             executed: None,
+        argv: None,
             status: "error".to_string(),
+            now_playing: None,
+            clipboard: None,
+            confirmation: None,
             error: Some("Could not parse".to_string()),
         };
 
@@ -138,4 +319,61 @@ mod tests {
         assert!(json.contains("error"));
         assert!(json.contains("Could not parse"));
     }
+
+    #[test]
+    fn test_json_output_candidates() {
+        let interpretation = parse_intent_ranked("open nots", &ParseOptions::default(), 3).unwrap();
+        let output = JsonOutput::skipped("open nots", &interpretation[0]).with_candidates(interpretation);
+
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["confidence"], json["candidates"][0]["confidence"]);
+        assert_eq!(json["candidates"][0]["action"]["type"], "OpenApp");
+        assert_eq!(json["candidates"][0]["rule"], "open");
+    }
+
+    #[test]
+    fn test_json_output_needs_confirmation() {
+        let interpretation = parse_intent_ranked("kill zoom", &ParseOptions::default(), 1).unwrap().remove(0);
+        let plan = CommandPlan::new("pkill").args(["-KILL", "-x", "zoom"]);
+        let output = JsonOutput::needs_confirmation("kill zoom", &interpretation, &plan, "Force quit Zoom?");
+
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["status"], "needs_confirmation");
+        assert_eq!(json["confirmation"], "Force quit Zoom?");
+        assert_eq!(json["executed"], "pkill -KILL -x zoom");
+    }
+
+    #[test]
+    fn test_sequence_status() {
+        let ok = || JsonOutput {
+            status: "success".to_string(),
+            ..JsonOutput::error("mute", "")
+        };
+        let failed = || JsonOutput {
+            status: "failed".to_string(),
+            ..JsonOutput::error("mute", "")
+        };
+
+        assert_eq!(JsonSequenceOutput::new("x", vec![ok(), ok()]).status, "success");
+        assert_eq!(JsonSequenceOutput::new("x", vec![ok(), failed()]).status, "failed");
+        assert_eq!(
+            JsonSequenceOutput::new("x", vec![ok(), JsonOutput::error("x", "boom")]).status,
+            "failed"
+        );
+
+        let waiting = || JsonOutput {
+            status: "needs_confirmation".to_string(),
+            ..JsonOutput::error("kill zoom", "")
+        };
+        assert_eq!(JsonSequenceOutput::new("x", vec![ok(), waiting()]).status, "needs_confirmation");
+        assert_eq!(JsonSequenceOutput::new("x", vec![waiting(), failed()]).status, "failed");
+    }
+
+    #[test]
+    fn test_sequence_serialization() {
+        let output = JsonSequenceOutput::new("mute and unmute", vec![JsonOutput::error("mute", "boom")]);
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains("\"steps\":["));
+        assert!(json.contains("boom"));
+    }
 }
@@ -0,0 +1,201 @@
+//! Interactive mode for Luna.
+//!
+//! `luna repl` (or `luna` with no command) reads commands line by line, with
+//! line editing and history. Config, the app catalog and the executor are
+//! loaded once for the whole session, and a `Session` carries context from
+//! one line to the next: "it" names the last app and "again" repeats the
+//! last command.
+
+use crate::cli::Cli;
+use crate::{load_luna, load_speaker, report_parse_error, run_steps};
+use luna::UtteranceStep;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+/// Prompt shown before each line when reading from a terminal.
+const PROMPT: &str = "luna> ";
+
+/// Lines that end the session (as does Ctrl-D).
+const EXIT_COMMANDS: &[&str] = &["exit", ":q", ":quit"];
+
+/// Lines that run the previous command again.
+const REPEAT_COMMANDS: &[&str] = &["again", "do that again", "do it again", "repeat", "repeat that", "one more time"];
+
+/// Words that refer back to the last app ("quit it").
+const PRONOUNS: &[&str] = &["it", "that"];
+
+/// Verbs whose object can be a pronoun for the last app.
+const APP_VERBS: &[&str] = &[
+    "open", "launch", "start", "quit", "close", "kill", "force", "hide", "minimize", "focus", "switch",
+    "bring",
+];
+
+/// Context kept between the lines of an interactive session.
+#[derive(Debug, Default)]
+pub struct Session {
+    /// The last line that parsed, after resolution
+    last_input: Option<String>,
+    /// The app named by the most recent app action
+    last_app: Option<String>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rewrite a line using the session's context: "again" becomes the last
+    /// command and a trailing "it"/"that" after an app verb becomes the last
+    /// app ("hide it" -> "hide Safari"). Other lines are returned trimmed.
+    pub fn resolve(&self, line: &str) -> String {
+        let line = line.trim();
+        let bare = line.trim_end_matches(['.', '!']).to_lowercase();
+
+        if REPEAT_COMMANDS.contains(&bare.as_str()) {
+            if let Some(ref last) = self.last_input {
+                return last.clone();
+            }
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let refers_to_app = words.len() >= 2
+            && APP_VERBS.contains(&words[0].to_lowercase().as_str())
+            && PRONOUNS.contains(&words[words.len() - 1].to_lowercase().as_str());
+        match self.last_app {
+            Some(ref app) if refers_to_app => format!("{} {}", words[..words.len() - 1].join(" "), app),
+            _ => line.to_string(),
+        }
+    }
+
+    /// Remember a line that parsed into `steps`.
+    pub fn record(&mut self, input: &str, steps: &[UtteranceStep]) {
+        self.last_input = Some(input.to_string());
+        if let Some(app) = steps.iter().rev().find_map(|step| step.interpretation.action.app_name()) {
+            self.last_app = Some(app.to_string());
+        }
+    }
+}
+
+/// Where the REPL keeps its history (`$XDG_STATE_HOME/luna/history`).
+pub fn history_path() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("state")))
+        .map(|dir| dir.join("luna").join("history"))
+}
+
+/// Run the interactive loop until "exit" or end of input.
+///
+/// Each line is handled like a one-shot command with the same flags; a
+/// failing line is reported and the session carries on.
+pub fn run(cli: &Cli) -> Result<(), i32> {
+    let mut editor = DefaultEditor::new().map_err(|e| {
+        eprintln!("Error: could not start the line editor: {}", e);
+        1
+    })?;
+    let history = history_path();
+    if let Some(ref path) = history {
+        // A missing history file just means a first session
+        let _ = editor.load_history(path);
+    }
+
+    let interactive = io::stdin().is_terminal();
+    let prompt = if interactive { PROMPT } else { "" };
+    if interactive && !cli.json {
+        eprintln!("Luna {} - type a command, or \"exit\" to leave.", env!("CARGO_PKG_VERSION"));
+    }
+
+    let speaker = if cli.speak { load_speaker() } else { None };
+    let speaker = speaker.as_deref();
+    let luna = load_luna();
+    let mut session = Session::new();
+
+    loop {
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C abandons the current line only
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return Err(1);
+            }
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        if EXIT_COMMANDS.contains(&line.to_lowercase().as_str()) {
+            break;
+        }
+
+        let input = session.resolve(line);
+        match luna.parse(&input) {
+            Ok(steps) => {
+                session.record(&input, &steps);
+                // Failures are already reported; the session goes on
+                let _ = run_steps(cli, &luna, &input, &steps, speaker);
+            }
+            Err(e) => report_parse_error(cli, &input, &e, speaker),
+        }
+    }
+
+    if let Some(ref path) = history {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("Warning: could not save history to {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luna::intent::{parse_utterance, ParseOptions};
+
+    fn record(session: &mut Session, input: &str) {
+        let steps = parse_utterance(input, &ParseOptions::default()).unwrap();
+        session.record(input, &steps);
+    }
+
+    #[test]
+    fn test_resolve_without_context() {
+        let session = Session::new();
+        assert_eq!(session.resolve("  open safari "), "open safari");
+        assert_eq!(session.resolve("quit it"), "quit it");
+        assert_eq!(session.resolve("again"), "again");
+    }
+
+    #[test]
+    fn test_resolve_pronoun_to_last_app() {
+        let mut session = Session::new();
+        record(&mut session, "open safari");
+        assert_eq!(session.last_app.as_deref(), Some("Safari"));
+        assert_eq!(session.resolve("hide it"), "hide Safari");
+        assert_eq!(session.resolve("switch to that"), "switch to Safari");
+        // "it" that isn't an app's name is left alone
+        assert_eq!(session.resolve("turn it up"), "turn it up");
+        assert_eq!(session.resolve("copy it"), "copy it");
+    }
+
+    #[test]
+    fn test_resolve_again_repeats_last_input() {
+        let mut session = Session::new();
+        record(&mut session, "open slack");
+        record(&mut session, "volume up");
+        assert_eq!(session.resolve("again"), "volume up");
+        assert_eq!(session.resolve("Do that again."), "volume up");
+        // The last app survives commands without one
+        assert_eq!(session.last_app.as_deref(), Some("Slack"));
+    }
+}
@@ -0,0 +1,330 @@
+//! Spoken feedback for Luna.
+//!
+//! With `--speak`, each result is turned into a short sentence ("Opening
+//! Safari", "Volume set to 40") and read aloud by a `Speaker`. Phrases are
+//! built separately from the audio backends so they can be tested anywhere.
+
+use crate::exec::{is_on_path, CommandPlan, CommandRunner, ExecError, ExecResult, SystemRunner};
+use crate::intent::{Action, TextTransform};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Longest clipboard excerpt read aloud, in characters.
+const MAX_SPOKEN_CLIPBOARD: usize = 120;
+
+/// Spoken when a command could not be understood.
+pub const NOT_UNDERSTOOD_PHRASE: &str = "Sorry, I didn't understand that.";
+
+/// The sentence to speak after running an action.
+pub fn result_phrase(action: &Action, result: &ExecResult) -> String {
+    if !result.success {
+        return failure_phrase(action);
+    }
+
+    match action {
+        Action::OpenApp { name } => format!("Opening {}", name),
+        Action::QuitApp { name } => format!("Quitting {}", name),
+        Action::ForceQuitApp { name } => format!("Force quitting {}", name),
+        Action::HideApp { name } => format!("Hiding {}", name),
+        Action::FocusApp { name } => format!("Switching to {}", name),
+        Action::OpenUrl { url } => format!("Opening {}", url_host(url)),
+        Action::OpenPath { path, reveal: false } => format!("Opening {}", file_name(path)),
+        Action::OpenPath { path, reveal: true } => format!("Showing {}", file_name(path)),
+        Action::WebSearch { query, engine } => format!("Searching {} for {}", engine, query),
+        Action::SetVolume { level } => format!("Volume set to {}", level),
+        Action::AdjustVolume { delta } => direction_phrase("Volume", *delta),
+        Action::Mute => "Muted".to_string(),
+        Action::Unmute => "Unmuted".to_string(),
+        Action::SetBrightness { level } => format!("Brightness set to {}", level),
+        Action::AdjustBrightness { delta } => direction_phrase("Brightness", *delta),
+        Action::Play => "Playing".to_string(),
+        Action::Pause => "Paused".to_string(),
+        Action::TogglePlayback => "Toggled playback".to_string(),
+        Action::NextTrack => "Next track".to_string(),
+        Action::PreviousTrack => "Previous track".to_string(),
+        Action::NowPlaying => match result.now_playing {
+            Some(ref track) => match track.artist {
+                Some(ref artist) => format!("Now playing {} by {}", track.title, artist),
+                None => format!("Now playing {}", track.title),
+            },
+            None => "Nothing is playing".to_string(),
+        },
+        Action::LockScreen => "Locking the screen".to_string(),
+        Action::Sleep => "Going to sleep".to_string(),
+        Action::Restart => "Restarting".to_string(),
+        Action::Shutdown => "Shutting down".to_string(),
+        Action::LogOut => "Logging out".to_string(),
+        Action::CopyText { .. } => "Copied to the clipboard".to_string(),
+        Action::ReadClipboard => match result.clipboard.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => format!("Your clipboard says: {}", excerpt(text)),
+            _ => "Your clipboard is empty".to_string(),
+        },
+        Action::TransformClipboard { transform } => transform_phrase(*transform),
+        Action::Notify { .. } => "Notification sent".to_string(),
+    }
+}
+
+/// The sentence to speak when an action ran but failed.
+pub fn failure_phrase(action: &Action) -> String {
+    match action.app_name() {
+        Some(name) => format!("Sorry, that didn't work for {}", name),
+        None => "Sorry, that didn't work".to_string(),
+    }
+}
+
+/// "Volume up" / "Brightness down".
+fn direction_phrase(setting: &str, delta: i8) -> String {
+    format!("{} {}", setting, if delta < 0 { "down" } else { "up" })
+}
+
+/// What a clipboard transform did.
+fn transform_phrase(transform: TextTransform) -> String {
+    match transform {
+        TextTransform::Lowercase => "Clipboard made lowercase",
+        TextTransform::Uppercase => "Clipboard made uppercase",
+        TextTransform::TitleCase => "Clipboard made title case",
+        TextTransform::Trim => "Clipboard trimmed",
+        TextTransform::StripFormatting => "Clipboard formatting removed",
+        TextTransform::UrlDecode => "Clipboard URL decoded",
+    }
+    .to_string()
+}
+
+/// The host of a URL, without "www." ("https://www.github.com/x" -> "github.com").
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// The last component of a path, or the whole path if it has none.
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+/// The start of `text`, cut at a word boundary if it is long.
+fn excerpt(text: &str) -> String {
+    if text.chars().count() <= MAX_SPOKEN_CLIPBOARD {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_SPOKEN_CLIPBOARD).collect();
+    let cut = cut.rsplit_once(char::is_whitespace).map_or(cut.as_str(), |(start, _)| start);
+    format!("{}, and more", cut.trim_end())
+}
+
+/// Reads sentences aloud.
+pub trait Speaker {
+    /// Speak `text`, returning once it has been spoken.
+    fn speak(&self, text: &str) -> Result<(), ExecError>;
+}
+
+/// Text-to-speech programs Luna can speak through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeechEngine {
+    /// macOS `say`
+    Say,
+    /// `espeak-ng` (Linux)
+    EspeakNg,
+}
+
+impl SpeechEngine {
+    /// The first engine installed on `PATH`, if any.
+    pub fn detect() -> Option<Self> {
+        [SpeechEngine::Say, SpeechEngine::EspeakNg]
+            .into_iter()
+            .find(|engine| is_on_path(engine.program()))
+    }
+
+    /// Name of the executable for this engine.
+    pub fn program(self) -> &'static str {
+        match self {
+            SpeechEngine::Say => "say",
+            SpeechEngine::EspeakNg => "espeak-ng",
+        }
+    }
+
+    /// Plan that speaks `text`, passed on stdin so it can't be read as options.
+    pub fn plan(self, text: &str) -> CommandPlan {
+        let plan = match self {
+            SpeechEngine::Say => CommandPlan::new("say").args(["-f", "-"]),
+            SpeechEngine::EspeakNg => CommandPlan::new("espeak-ng").arg("--stdin"),
+        };
+        plan.stdin(text)
+    }
+}
+
+/// Speaks through a text-to-speech program.
+#[derive(Debug)]
+pub struct CommandSpeaker<R: CommandRunner = SystemRunner> {
+    runner: R,
+    engine: SpeechEngine,
+}
+
+impl CommandSpeaker {
+    /// Speak through `engine`, running real processes.
+    pub fn new(engine: SpeechEngine) -> Self {
+        Self::with_runner(SystemRunner, engine)
+    }
+}
+
+impl<R: CommandRunner> CommandSpeaker<R> {
+    /// Speak through `engine`, running commands through `runner`.
+    pub fn with_runner(runner: R, engine: SpeechEngine) -> Self {
+        Self { runner, engine }
+    }
+}
+
+impl<R: CommandRunner> Speaker for CommandSpeaker<R> {
+    fn speak(&self, text: &str) -> Result<(), ExecError> {
+        let output = self.runner.run(&self.engine.plan(text))?;
+        if output.success {
+            Ok(())
+        } else {
+            Err(ExecError::CommandFailed(format!(
+                "{} failed: {}",
+                self.engine.program(),
+                output.stderr.trim()
+            )))
+        }
+    }
+}
+
+/// Speaks nothing, remembering each sentence instead.
+#[derive(Debug, Default)]
+pub struct NullSpeaker {
+    spoken: Mutex<Vec<String>>,
+}
+
+impl NullSpeaker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every sentence passed to `speak`, in order.
+    pub fn spoken(&self) -> Vec<String> {
+        self.spoken.lock().unwrap().clone()
+    }
+}
+
+impl Speaker for NullSpeaker {
+    fn speak(&self, text: &str) -> Result<(), ExecError> {
+        self.spoken.lock().unwrap().push(text.to_string());
+        Ok(())
+    }
+}
+
+/// A speaker for the installed text-to-speech program, or `None` if there
+/// is none.
+pub fn default_speaker() -> Option<Box<dyn Speaker>> {
+    SpeechEngine::detect().map(|engine| Box::new(CommandSpeaker::new(engine)) as Box<dyn Speaker>)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::{CommandOutput, NowPlaying, RecordingRunner};
+
+    fn succeeded() -> ExecResult {
+        ExecResult {
+            command: CommandPlan::new("true"),
+            success: true,
+            output: None,
+            now_playing: None,
+            clipboard: None,
+        }
+    }
+
+    #[test]
+    fn test_result_phrase() {
+        let phrase = |action: Action| result_phrase(&action, &succeeded());
+        assert_eq!(phrase(Action::OpenApp { name: "Safari".to_string() }), "Opening Safari");
+        assert_eq!(phrase(Action::SetVolume { level: 40 }), "Volume set to 40");
+        assert_eq!(phrase(Action::AdjustVolume { delta: -10 }), "Volume down");
+        assert_eq!(phrase(Action::AdjustBrightness { delta: 5 }), "Brightness up");
+        assert_eq!(
+            phrase(Action::OpenUrl { url: "https://www.github.com/rust-lang".to_string() }),
+            "Opening github.com"
+        );
+        assert_eq!(
+            phrase(Action::OpenPath { path: "/home/luna/Downloads".to_string(), reveal: false }),
+            "Opening Downloads"
+        );
+        assert_eq!(
+            phrase(Action::WebSearch { query: "rust lifetimes".to_string(), engine: "google".to_string() }),
+            "Searching google for rust lifetimes"
+        );
+        assert_eq!(
+            phrase(Action::TransformClipboard { transform: TextTransform::Lowercase }),
+            "Clipboard made lowercase"
+        );
+    }
+
+    #[test]
+    fn test_result_phrase_uses_reported_data() {
+        let mut result = succeeded();
+        assert_eq!(result_phrase(&Action::NowPlaying, &result), "Nothing is playing");
+        result.now_playing = Some(NowPlaying {
+            player: "Spotify".to_string(),
+            title: "So What".to_string(),
+            artist: Some("Miles Davis".to_string()),
+            album: None,
+        });
+        assert_eq!(result_phrase(&Action::NowPlaying, &result), "Now playing So What by Miles Davis");
+
+        result.clipboard = Some("  \n".to_string());
+        assert_eq!(result_phrase(&Action::ReadClipboard, &result), "Your clipboard is empty");
+        result.clipboard = Some("me@example.com\n".to_string());
+        assert_eq!(result_phrase(&Action::ReadClipboard, &result), "Your clipboard says: me@example.com");
+    }
+
+    #[test]
+    fn test_result_phrase_failure() {
+        let mut result = succeeded();
+        result.success = false;
+        assert_eq!(
+            result_phrase(&Action::OpenApp { name: "Nope".to_string() }, &result),
+            "Sorry, that didn't work for Nope"
+        );
+        assert_eq!(result_phrase(&Action::Mute, &result), "Sorry, that didn't work");
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("short"), "short");
+        let long = "word ".repeat(40);
+        let spoken = excerpt(&long);
+        assert!(spoken.ends_with("word, and more"));
+        assert!(spoken.chars().count() <= MAX_SPOKEN_CLIPBOARD + ", and more".len());
+    }
+
+    #[test]
+    fn test_command_speaker_passes_text_on_stdin() {
+        let runner = RecordingRunner::new();
+        CommandSpeaker::with_runner(&runner, SpeechEngine::EspeakNg)
+            .speak("-v Opening Safari")
+            .unwrap();
+        assert_eq!(
+            runner.calls(),
+            vec![CommandPlan::new("espeak-ng").arg("--stdin").stdin("-v Opening Safari")]
+        );
+        assert_eq!(SpeechEngine::Say.plan("hi").to_string(), "printf '%s' hi | say -f -");
+    }
+
+    #[test]
+    fn test_command_speaker_failure() {
+        let runner = RecordingRunner::new();
+        runner.push_output(CommandOutput::failure(1, "no audio device"));
+        let err = CommandSpeaker::with_runner(&runner, SpeechEngine::Say).speak("hi").unwrap_err();
+        assert!(matches!(err, ExecError::CommandFailed(message) if message.contains("no audio device")));
+    }
+
+    #[test]
+    fn test_null_speaker_records() {
+        let speaker = NullSpeaker::new();
+        speaker.speak("Muted").unwrap();
+        speaker.speak(NOT_UNDERSTOOD_PHRASE).unwrap();
+        assert_eq!(speaker.spoken(), vec!["Muted", NOT_UNDERSTOOD_PHRASE]);
+    }
+}